@@ -0,0 +1,165 @@
+use crate::*;
+use glib::WeakRef;
+use gtk::RevealerTransitionType;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+thread_local! {
+    /// revealers and their unscaled duration, kept in step with gtk's animation setting
+    static REVEALERS: RefCell<Vec<(WeakRef<Revealer>, u32)>> = const { RefCell::new(Vec::new()) };
+    static WATCHING: Cell<bool> = const { Cell::new(false) };
+}
+
+#[derive(Clone, Copy)]
+pub enum Transition {
+    Fade,
+    /// grows to the right
+    Slide,
+    /// grows along the bar towards its start, left or up
+    Expand,
+}
+
+impl Transition {
+    fn revealer_type(self) -> RevealerTransitionType {
+        match self {
+            Self::Fade => Crossfade,
+            Self::Slide => SlideRight,
+            Self::Expand if CONFIG.bar.position.vertical() => RevealerTransitionType::SlideUp,
+            Self::Expand => SlideLeft,
+        }
+    }
+}
+
+/// with reduce motion from the config or gtk's own animations switched off, nothing moves
+pub fn reduced() -> bool {
+    CONFIG.animation.reduce_motion
+        || gtk::Settings::default().is_some_and(|s| !s.is_gtk_enable_animations())
+}
+
+/// milliseconds after the configured scale, zero when motion is reduced
+pub fn duration(millis: u32) -> u32 {
+    if reduced() {
+        0
+    } else {
+        (millis as f64 * CONFIG.animation.scale.max(0.0)).round() as u32
+    }
+}
+
+/// sleeps for `millis` after the scale, right away when there's nothing to wait for
+pub async fn pause(millis: u32) {
+    let millis = duration(millis);
+    if millis > 0 {
+        sleep(Duration::from_millis(millis as u64)).await;
+    }
+}
+
+/// gives `revealer` the duration `millis` works out to, now and whenever animations
+/// are switched on or off in gtk's settings
+pub fn follow(revealer: &Revealer, millis: u32) {
+    revealer.set_transition_duration(duration(millis));
+    REVEALERS.with(|r| {
+        let mut revealers = r.borrow_mut();
+        revealers.retain(|(revealer, _)| revealer.upgrade().is_some());
+        revealers.push((revealer.downgrade(), millis));
+    });
+    if !WATCHING.get() {
+        if let Some(settings) = gtk::Settings::default() {
+            WATCHING.set(true);
+            settings.connect_gtk_enable_animations_notify(|_| update());
+        }
+    }
+}
+
+/// applies the current settings to every revealer that's still around
+fn update() {
+    REVEALERS.with(|r| {
+        r.borrow_mut()
+            .retain(|(revealer, millis)| match revealer.upgrade() {
+                Some(revealer) => {
+                    revealer.set_transition_duration(duration(*millis));
+                    true
+                }
+                None => false,
+            })
+    });
+}
+
+/// a revealer that follows the animation settings
+pub fn revealer(transition: Transition, millis: u32) -> Revealer {
+    let revealer = Revealer::builder()
+        .transition_type(transition.revealer_type())
+        .build();
+    follow(&revealer, millis);
+    revealer
+}
+
+/// nested revealers that show outside in and hide inside out, like a slide making room
+/// before the fade, a newer `reveal` cancels the steps a running one hasn't taken yet
+#[derive(Debug, Clone)]
+pub struct Sequence {
+    pub widget: Revealer,
+    /// outermost first
+    stages: Rc<Vec<Revealer>>,
+    generation: Rc<Cell<u64>>,
+}
+
+impl Sequence {
+    pub fn new<W>(child: &W, stages: &[(Transition, u32)]) -> Self
+    where
+        W: IsA<Widget>,
+    {
+        let stages: Vec<Revealer> = stages
+            .iter()
+            .map(|(transition, millis)| revealer(*transition, *millis))
+            .collect();
+        for pair in stages.windows(2) {
+            pair[0].set_child(Some(&pair[1]));
+        }
+        let widget = stages.first().expect("a sequence needs a stage").clone();
+        stages.last().unwrap().set_child(Some(child));
+        Self {
+            widget,
+            stages: Rc::new(stages),
+            generation: Rc::new(Cell::new(0)),
+        }
+    }
+    /// starts revealing or hiding without waiting for it
+    pub fn reveal(&self, reveal: bool) {
+        spawn_future_local(clone! {
+            #[strong(rename_to = this)] self,
+            async move {
+                this.animate(reveal).await;
+            }
+        });
+    }
+    /// each stage starts once the previous one is partly done, a third of the way in when
+    /// showing and halfway when hiding, returns false when a newer call took over
+    pub async fn animate(&self, reveal: bool) -> bool {
+        let generation = self.generation.get() + 1;
+        self.generation.set(generation);
+        let stages: Vec<&Revealer> = if reveal {
+            self.stages.iter().collect()
+        } else {
+            self.stages.iter().rev().collect()
+        };
+        for (i, stage) in stages.iter().enumerate() {
+            if i > 0 {
+                // read back so a change in the settings since `new` counts
+                let previous = stages[i - 1].transition_duration();
+                let wait = if reveal { previous / 3 } else { previous / 2 };
+                // already scaled, so no `pause` here
+                if wait > 0 {
+                    sleep(Duration::from_millis(wait as u64)).await;
+                }
+                if self.generation.get() != generation {
+                    return false;
+                }
+            }
+            stage.set_reveal_child(reveal);
+        }
+        true
+    }
+    pub fn revealed(&self) -> bool {
+        self.stages.iter().any(|s| s.reveals_child())
+    }
+}
@@ -0,0 +1,161 @@
+use crate::*;
+use async_broadcast::{broadcast, InactiveReceiver, Receiver, Sender};
+use lazy_static::lazy_static;
+use libs::animation;
+use libs::config::Position;
+use libs::shared_widget::orientation;
+use std::cell::Cell;
+use std::rc::Rc;
+
+lazy_static! {
+    static ref ATTENTION: (Sender<()>, InactiveReceiver<()>) = {
+        let (mut sender, receiver) = broadcast(1);
+        sender.set_overflow(true);
+        (sender, receiver.deactivate())
+    };
+}
+
+/// ask for the bar to be shown for a moment, does nothing unless auto-hide is on
+pub fn request_attention() {
+    let _ = ATTENTION.0.try_broadcast(());
+}
+
+fn attention() -> Receiver<()> {
+    ATTENTION.1.activate_cloned()
+}
+
+#[derive(Clone)]
+pub struct AutoHide {
+    window: ApplicationWindow,
+    revealer: Revealer,
+    hover: EventControllerMotion,
+    width: i32,
+    height: i32,
+    // bumped on every show/hide so stale timeouts know to give up
+    generation: Rc<Cell<u64>>,
+}
+
+impl AutoHide {
+    pub fn new(window: &ApplicationWindow, mut event_listener: Receiver<String>) -> Self {
+        let config = &CONFIG.autohide;
+        // slide towards the edge the bar is anchored to
+        let transition = match CONFIG.bar.position {
+            Position::Top => gtk::RevealerTransitionType::SlideDown,
+            Position::Bottom => gtk::RevealerTransitionType::SlideUp,
+            Position::Left => gtk::RevealerTransitionType::SlideRight,
+            Position::Right => gtk::RevealerTransitionType::SlideLeft,
+        };
+        let revealer = Revealer::builder()
+            .transition_type(transition)
+            .reveal_child(true)
+            .build();
+        animation::follow(&revealer, config.duration);
+        let hotzone = Box::new(orientation(), 0);
+        if CONFIG.bar.position.vertical() {
+            hotzone.set_size_request(config.hotzone, -1);
+        } else {
+            hotzone.set_size_request(-1, config.hotzone);
+        }
+        hotzone.append(&revealer);
+        let child = window.child();
+        window.set_child(None::<&Widget>);
+        revealer.set_child(child.as_ref());
+        window.set_child(Some(&hotzone));
+        let hover = EventControllerMotion::new();
+        window.add_controller(hover.clone());
+        let this = Self {
+            window: window.clone(),
+            revealer,
+            hover: hover.clone(),
+            width: window.default_width(),
+            height: window.default_height(),
+            generation: Rc::new(Cell::new(0)),
+        };
+        hover.connect_enter(clone! {
+            #[strong] this,
+            move |_, _, _| this.show()
+        });
+        hover.connect_leave(clone! {
+            #[strong] this,
+            move |_| this.hide_later()
+        });
+        if config.show_on_workspace_switch {
+            spawn_future_local(clone! {
+                #[strong] this,
+                async move {
+                    loop {
+                        if let Ok(event) = event_listener.recv().await {
+                            if event.starts_with("workspacev2>>") {
+                                this.peek();
+                            }
+                        }
+                    }
+                }
+            });
+        }
+        if config.show_on_attention {
+            let mut attention = attention();
+            spawn_future_local(clone! {
+                #[strong] this,
+                async move {
+                    while attention.recv().await.is_ok() {
+                        this.peek();
+                    }
+                }
+            });
+        }
+        this.hide_later();
+        this
+    }
+    fn bump(&self) -> u64 {
+        let generation = self.generation.get() + 1;
+        self.generation.set(generation);
+        generation
+    }
+    pub fn show(&self) {
+        self.bump();
+        self.window.set_default_size(self.width, self.height);
+        if CONFIG.bar.exclusive_zone {
+            self.window.auto_exclusive_zone_enable();
+        }
+        self.revealer.set_reveal_child(true);
+    }
+    pub fn hide(&self) {
+        let generation = self.bump();
+        self.revealer.set_reveal_child(false);
+        spawn_future_local(clone! {
+            #[strong(rename_to = this)] self,
+            async move {
+                animation::pause(CONFIG.autohide.duration).await;
+                if this.generation.get() == generation {
+                    if CONFIG.bar.position.vertical() {
+                        this.window.set_default_size(CONFIG.autohide.hotzone, this.height);
+                    } else {
+                        this.window.set_default_size(this.width, CONFIG.autohide.hotzone);
+                    }
+                    this.window.set_exclusive_zone(0);
+                }
+            }
+        });
+    }
+    /// hide after the configured delay unless something shows the bar again in the meantime
+    fn hide_later(&self) {
+        let generation = self.bump();
+        spawn_future_local(clone! {
+            #[strong(rename_to = this)] self,
+            async move {
+                sleep(Duration::from_millis(CONFIG.autohide.delay)).await;
+                if this.generation.get() == generation && !this.hover.contains_pointer() {
+                    this.hide();
+                }
+            }
+        });
+    }
+    /// show the bar and hide it again once the delay has passed
+    fn peek(&self) {
+        if !self.hover.contains_pointer() {
+            self.show();
+            self.hide_later();
+        }
+    }
+}
@@ -0,0 +1,662 @@
+use super::format::Format;
+use lazy_static::lazy_static;
+use serde::Deserialize;
+use serde_json::from_str;
+use std::{collections::HashMap, env::var, path::PathBuf};
+
+lazy_static! {
+    static ref CONFIG_PATH: PathBuf = var("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from(var("HOME").unwrap()).join(".config"))
+        .join("gtk-bar")
+        .join("config.json");
+    pub static ref CONFIG: Config = Config::load();
+}
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct Config {
+    /// the monitor the bar lives on, by connector name
+    pub monitor: Option<String>,
+    pub bar: Bar,
+    pub autohide: AutoHide,
+    pub background: Background,
+    pub workspaces: Workspaces,
+    /// `{name}`, `{kind}` and `{strength}`, icons are picked by kind and strength
+    pub network: Format,
+    /// `{devices}` and `{count}` of the connected ones
+    pub bluetooth: Format,
+    pub keyboard: Keyboard,
+    pub battery: Format,
+    pub volume: Format,
+    /// strftime codes work in the template too
+    pub clock: Format,
+    pub throughput: Throughput,
+    pub cpu: Resource,
+    pub memory: Resource,
+    pub load: Resource,
+    pub temperature: Temperature,
+    pub disk: Disk,
+    pub backlight: Backlight,
+    pub notifications: Notifications,
+    pub power: Power,
+    pub idle: Idle,
+    pub power_profiles: PowerProfiles,
+    pub custom: Vec<Custom>,
+    pub privacy: Privacy,
+    pub tooltips: Tooltips,
+    pub icons: IconStyles,
+    pub animation: Animation,
+}
+
+#[derive(Deserialize, Default, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Position {
+    Top,
+    #[default]
+    Bottom,
+    Left,
+    Right,
+}
+
+impl Position {
+    pub fn vertical(self) -> bool {
+        matches!(self, Self::Left | Self::Right)
+    }
+    pub fn css_class(self) -> &'static str {
+        match self {
+            Self::Top => "top",
+            Self::Bottom => "bottom",
+            Self::Left => "left",
+            Self::Right => "right",
+        }
+    }
+}
+
+#[derive(Deserialize, Default, Debug, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum BarLayer {
+    Background,
+    Bottom,
+    #[default]
+    Top,
+    Overlay,
+}
+
+#[derive(Deserialize, Default, Debug, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyboardInteractivity {
+    #[default]
+    None,
+    Exclusive,
+    OnDemand,
+}
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct Margins {
+    pub top: i32,
+    pub bottom: i32,
+    pub left: i32,
+    pub right: i32,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct Bar {
+    pub position: Position,
+    /// thickness of the bar, that's its width when it's on the left or right
+    pub height: i32,
+    /// length of the bar along its edge
+    pub width: i32,
+    pub margins: Margins,
+    pub layer: BarLayer,
+    /// reserve space so windows don't go under the bar
+    pub exclusive_zone: bool,
+    pub keyboard_interactivity: KeyboardInteractivity,
+}
+
+impl Default for Bar {
+    fn default() -> Self {
+        Self {
+            position: Position::default(),
+            height: 50,
+            width: 1920,
+            margins: Margins::default(),
+            layer: BarLayer::default(),
+            exclusive_zone: true,
+            keyboard_interactivity: KeyboardInteractivity::default(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct AutoHide {
+    pub enabled: bool,
+    /// milliseconds to wait after the pointer leaves before hiding
+    pub delay: u64,
+    /// milliseconds the slide takes
+    pub duration: u32,
+    /// height in pixels of the strip left at the edge to bring the bar back
+    pub hotzone: i32,
+    pub show_on_workspace_switch: bool,
+    /// show when a module asks for it, e.g. on volume change
+    pub show_on_attention: bool,
+}
+
+impl Default for AutoHide {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            delay: 1000,
+            duration: 250,
+            hotzone: 2,
+            show_on_workspace_switch: true,
+            show_on_attention: true,
+        }
+    }
+}
+
+#[derive(Deserialize, Default, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum BackgroundMode {
+    /// opaque while a tiled window is on the workspace
+    #[default]
+    Auto,
+    Opaque,
+    Transparent,
+    /// opaque only while a window is fullscreen
+    Fullscreen,
+}
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct Background {
+    pub mode: BackgroundMode,
+    /// window classes that never make the bar opaque
+    pub ignore_classes: Vec<String>,
+    /// floating windows make the bar opaque too
+    pub count_floating: bool,
+    /// follow the workspace on `monitor` instead of the focused one
+    pub per_monitor: bool,
+}
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct Workspaces {
+    /// skip empty workspaces when scrolling
+    pub scroll_occupied_only: bool,
+    /// only scroll through workspaces on the focused monitor
+    pub scroll_per_monitor: bool,
+    /// workspace ids that stay visible even when they're empty
+    pub persistent: Vec<i32>,
+    /// display names or glyphs keyed by workspace id or name
+    pub labels: HashMap<String, String>,
+    /// show the name of named workspaces that have no label
+    pub show_names: bool,
+    /// keep workspaces on the same monitor next to each other
+    pub group_by_monitor: bool,
+}
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct Keyboard {
+    /// short names keyed by xkb layout name, e.g. `"English (US)": "us"`
+    pub labels: HashMap<String, String>,
+    /// `{short}` from the labels above and the full `{layout}`
+    #[serde(flatten)]
+    pub label: Format,
+}
+
+#[derive(Deserialize, Default, Debug, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum RateUnit {
+    #[default]
+    Bytes,
+    Bits,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct Throughput {
+    pub enabled: bool,
+    /// interface to watch, the one with the default route when unset
+    pub interface: Option<String>,
+    /// milliseconds between updates, at least 100
+    pub interval: u64,
+    pub unit: RateUnit,
+    pub graph: bool,
+    /// how many updates the graph shows
+    pub history: usize,
+    /// used for both directions, `{rate}` as text, `{bytes}` per second and `{direction}`
+    #[serde(flatten)]
+    pub label: Format,
+}
+
+impl Default for Throughput {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interface: None,
+            interval: 1000,
+            unit: RateUnit::default(),
+            graph: true,
+            history: 20,
+            label: Format::default(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct Resource {
+    pub enabled: bool,
+    /// milliseconds between updates, at least 100
+    pub interval: u64,
+    /// percentage at which the module gets the `warning` class, for load that's per core
+    pub warning: f64,
+    /// percentage at which the module gets the `critical` class
+    pub critical: f64,
+    pub graph: bool,
+    /// how many updates the graph shows
+    pub history: usize,
+    /// cpu only, a small bar for every core
+    pub per_core: bool,
+    #[serde(flatten)]
+    pub label: Format,
+}
+
+impl Default for Resource {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval: 2000,
+            warning: 70.0,
+            critical: 90.0,
+            graph: false,
+            history: 20,
+            per_core: false,
+            label: Format::default(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct Temperature {
+    pub enabled: bool,
+    /// milliseconds between updates, at least 100
+    pub interval: u64,
+    /// sensors to show as `chip`, `label` or `chip/label`, the hottest one wins, all when empty
+    pub sensors: Vec<String>,
+    /// degrees celsius at which the module gets the `warning` class
+    pub warning: f64,
+    /// used for sensors that don't report their own critical temperature
+    pub critical: f64,
+    #[serde(flatten)]
+    pub label: Format,
+}
+
+impl Default for Temperature {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval: 5000,
+            sensors: Vec::new(),
+            warning: 70.0,
+            critical: 90.0,
+            label: Format::default(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct Disk {
+    pub enabled: bool,
+    /// mount points to show, one module each
+    pub mounts: Vec<String>,
+    /// milliseconds between updates, at least 100
+    pub interval: u64,
+    /// used percentage at which a module gets the `warning` class
+    pub warning: f64,
+    /// used percentage at which a module gets the `critical` class
+    pub critical: f64,
+    /// run with the mount point on click
+    pub file_manager: String,
+    #[serde(flatten)]
+    pub label: Format,
+}
+
+impl Default for Disk {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            mounts: vec!["/".to_string()],
+            interval: 30000,
+            warning: 80.0,
+            critical: 95.0,
+            file_manager: "xdg-open".to_string(),
+            label: Format::default(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct Backlight {
+    /// name under `/sys/class/backlight`, the first one when unset
+    pub device: Option<String>,
+    /// percent per scroll step
+    pub step: f64,
+    /// show the popup when the brightness changes
+    pub osd: bool,
+    #[serde(flatten)]
+    pub label: Format,
+}
+
+impl Default for Backlight {
+    fn default() -> Self {
+        Self {
+            device: None,
+            step: 5.0,
+            osd: true,
+            label: Format::default(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct Notifications {
+    /// run the notification daemon, another one can't be running at the same time
+    pub enabled: bool,
+    /// milliseconds a popup stays when the sender doesn't say
+    pub timeout: u32,
+    /// how many notifications the history keeps
+    pub history: usize,
+    /// start with do not disturb on
+    pub dnd: bool,
+}
+
+impl Default for Notifications {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            timeout: 5000,
+            history: 50,
+            dnd: false,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct Power {
+    pub enabled: bool,
+    /// ask again before logging out, suspending and the like
+    pub confirm: bool,
+    /// run through `sh -c` to lock, logind's `LockSession` when unset
+    pub lock_command: Option<String>,
+}
+
+impl Default for Power {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            confirm: true,
+            lock_command: None,
+        }
+    }
+}
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct Idle {
+    pub enabled: bool,
+    /// start with the inhibitor held
+    pub inhibit: bool,
+}
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct PowerProfiles {
+    /// the profile also shows up in the battery tooltip without the module
+    pub enabled: bool,
+}
+
+#[derive(Deserialize, Default, Debug, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum Side {
+    Left,
+    Center,
+    #[default]
+    Right,
+}
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct Privacy {
+    /// only shows up while the screen, a camera or a microphone is in use
+    pub enabled: bool,
+}
+
+/// a module driven by a user command, it prints plain lines (text, tooltip, class)
+/// or json with `text`, `tooltip`, `class` and `percentage`
+#[derive(Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct Custom {
+    /// used as css class and as `gtk-bar msg custom/<name>`, without one the module goes by
+    /// its place in the list, `custom/0` for the first
+    pub name: String,
+    /// run through `sh -c`
+    pub exec: String,
+    pub side: Side,
+    /// milliseconds between runs, for streams the delay before restarting one that exited
+    pub interval: Option<u64>,
+    /// run again on `SIGRTMIN+signal`
+    pub signal: Option<i32>,
+    /// keep the command running and update on every line it prints
+    pub stream: bool,
+    /// `{text}`, `{icon}` and `{percentage}`, icons are picked by `percentage`
+    /// and the first class is the state
+    #[serde(flatten)]
+    pub label: Format,
+    pub on_click: Option<String>,
+    pub on_middle_click: Option<String>,
+    pub on_right_click: Option<String>,
+    pub on_scroll_up: Option<String>,
+    pub on_scroll_down: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct Tooltips {
+    pub enabled: bool,
+    /// strftime format for the date in the clock's tooltip
+    pub date_format: String,
+}
+
+impl Default for Tooltips {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            date_format: "%A, %d %B %Y".to_string(),
+        }
+    }
+}
+
+#[derive(Deserialize, Default, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum IconStyle {
+    /// nerd font glyphs when a nerd font is installed, theme icons otherwise
+    #[default]
+    Auto,
+    Glyph,
+    /// named icons from the gtk icon theme, glyphs for the ones it doesn't have
+    Theme,
+}
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct IconStyles {
+    pub style: IconStyle,
+    /// overrides keyed by module, e.g. `"music": "glyph"`
+    pub modules: HashMap<String, IconStyle>,
+}
+
+impl IconStyles {
+    /// the modules that draw icons and so take an override
+    pub const MODULES: [&'static str; 19] = [
+        "backlight",
+        "battery",
+        "bluetooth",
+        "clock",
+        "cpu",
+        "disk",
+        "idle",
+        "load",
+        "memory",
+        "music",
+        "network",
+        "notifications",
+        "power",
+        "power_menu",
+        "power_profiles",
+        "privacy",
+        "temperature",
+        "throughput",
+        "volume",
+    ];
+    /// an override for a module that doesn't exist is most likely a typo
+    fn warn_unknown(&self) {
+        for module in self.modules.keys() {
+            if !Self::MODULES.contains(&module.as_str()) {
+                eprintln!(
+                    "{}: icons.modules: unknown module `{module}`, expected one of {}",
+                    CONFIG_PATH.display(),
+                    Self::MODULES.join(", ")
+                );
+            }
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct Animation {
+    /// multiplies every animation's duration, 0.5 is twice as fast
+    pub scale: f64,
+    /// no animations at all, also the case when gtk's animations are turned off
+    pub reduce_motion: bool,
+    pub durations: Durations,
+}
+
+impl Default for Animation {
+    fn default() -> Self {
+        Self {
+            scale: 1.0,
+            reduce_motion: false,
+            durations: Durations::default(),
+        }
+    }
+}
+
+/// milliseconds of each animation before the scale
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct Durations {
+    /// a new workspace making room, then fading in
+    pub workspace_slide: u32,
+    pub workspace_fade: u32,
+    /// the focused workspace growing
+    pub workspace_expand: u32,
+    /// the song title making room, then fading in
+    pub music_slide: u32,
+    pub music_fade: u32,
+    /// the music icon and the previous and next buttons
+    pub music_controls: u32,
+    /// the volume slider next to the icon
+    pub volume_slider: u32,
+    /// the bar background coming and going
+    pub background: u32,
+}
+
+impl Default for Durations {
+    fn default() -> Self {
+        Self {
+            workspace_slide: 250,
+            workspace_fade: 500,
+            workspace_expand: 1000,
+            music_slide: 750,
+            music_fade: 1500,
+            music_controls: 250,
+            volume_slider: 250,
+            background: 500,
+        }
+    }
+}
+
+impl Config {
+    fn load() -> Self {
+        let config = match std::fs::read_to_string(CONFIG_PATH.as_path()) {
+            Ok(config) => from_str(&config).unwrap_or_else(|e| {
+                eprintln!("{}: {e}", CONFIG_PATH.display());
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        };
+        config.icons.warn_unknown();
+        config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_config_uses_defaults() {
+        let config: Config = from_str("{}").unwrap();
+        assert_eq!(config.bar.position, Position::Bottom);
+        assert_eq!(config.bar.height, 50);
+        assert!(config.bar.exclusive_zone);
+        assert_eq!(config.throughput.interval, 1000);
+        assert!(!config.throughput.enabled);
+        assert!(config.tooltips.enabled);
+        assert_eq!(config.icons.style, IconStyle::Auto);
+        assert_eq!(config.animation.scale, 1.0);
+        assert_eq!(config.animation.durations.workspace_expand, 1000);
+        assert_eq!(config.animation.durations.music_fade, 1500);
+        assert!(config.custom.is_empty());
+    }
+
+    #[test]
+    fn partial_sections_keep_the_other_defaults() {
+        let config: Config =
+            from_str(r#"{"bar": {"height": 30}, "animation": {"durations": {"background": 0}}}"#)
+                .unwrap();
+        assert_eq!(config.bar.height, 30);
+        assert_eq!(config.bar.width, 1920);
+        assert_eq!(config.animation.durations.background, 0);
+        assert_eq!(config.animation.durations.workspace_slide, 250);
+    }
+
+    #[test]
+    fn labels_are_flattened_into_their_section() {
+        let config: Config = from_str(
+            r#"{
+                "keyboard": {"labels": {"English (US)": "us"}, "format": "{short}"},
+                "custom": [{"exec": "date", "format": "{icon} {text}", "icons": ["a", "b"]}]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(config.keyboard.labels["English (US)"], "us");
+        assert_eq!(config.keyboard.label.format.as_deref(), Some("{short}"));
+        let custom = &config.custom[0];
+        assert_eq!(custom.exec, "date");
+        assert_eq!(custom.label.format.as_deref(), Some("{icon} {text}"));
+        assert_eq!(custom.label.icon("default", 100.0), Some("b"));
+    }
+}
@@ -0,0 +1,107 @@
+use crate::*;
+use async_std::io::BufReader;
+use async_std::os::unix::net::{UnixListener, UnixStream};
+use lazy_static::lazy_static;
+use std::cell::RefCell;
+use std::future::Future;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::rc::Rc;
+
+lazy_static! {
+    pub static ref SOCKET_PATH: PathBuf = std::env::var("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| std::env::temp_dir())
+        .join("gtk-bar.sock");
+}
+
+type Reply = Pin<std::boxed::Box<dyn Future<Output = String>>>;
+type Handler = Rc<dyn Fn(&[&str]) -> Reply>;
+
+thread_local! {
+    static HANDLERS: RefCell<HashMap<String, Handler>> = RefCell::new(HashMap::new());
+}
+
+/// answer `<module> <args...>` lines sent to the control socket,
+/// the returned string goes back to the client
+pub fn register<F>(module: &str, handler: F)
+where
+    F: Fn(&[&str]) -> String + 'static,
+{
+    register_async(module, move |args: Vec<String>| {
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        std::future::ready(handler(&args))
+    });
+}
+
+/// like `register` for commands that only know their answer once some work is done
+pub fn register_async<F, R>(module: &str, handler: F)
+where
+    F: Fn(Vec<String>) -> R + 'static,
+    R: Future<Output = String> + 'static,
+{
+    let handler: Handler = Rc::new(move |args: &[&str]| -> Reply {
+        std::boxed::Box::pin(handler(args.iter().map(|a| a.to_string()).collect()))
+    });
+    HANDLERS.with(|h| h.borrow_mut().insert(module.to_string(), handler));
+}
+
+async fn dispatch(line: &str) -> String {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let Some((module, args)) = words.split_first() else {
+        return "error: empty command".to_string();
+    };
+    let handler = HANDLERS.with(|h| h.borrow().get(*module).cloned());
+    match handler {
+        Some(handler) => handler(args).await,
+        None => format!("error: unknown module {module}"),
+    }
+}
+
+async fn serve(stream: UnixStream) {
+    let mut lines = BufReader::new(&stream).lines();
+    while let Some(Ok(line)) = lines.next().await {
+        let reply = format!("{}\n", dispatch(&line).await);
+        if (&stream).write_all(reply.as_bytes()).await.is_err() {
+            break;
+        }
+    }
+}
+
+/// listens on `$XDG_RUNTIME_DIR/gtk-bar.sock`, one command per line
+pub fn listen() {
+    spawn_future_local(async {
+        // a bar that's still running answers, only a socket left behind by a crash is removed
+        if UnixStream::connect(SOCKET_PATH.as_path()).await.is_ok() {
+            eprintln!(
+                "control: {} is in use by another bar",
+                SOCKET_PATH.display()
+            );
+            return;
+        }
+        let _ = std::fs::remove_file(SOCKET_PATH.as_path());
+        let listener = match UnixListener::bind(SOCKET_PATH.as_path()).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                eprintln!("control: {}: {e}", SOCKET_PATH.display());
+                return;
+            }
+        };
+        let mut incoming = listener.incoming();
+        while let Some(stream) = incoming.next().await {
+            if let Ok(stream) = stream {
+                spawn_future_local(serve(stream));
+            }
+        }
+    });
+}
+
+/// `gtk-bar msg <module> <args...>` sends one command to the running bar and prints the reply
+pub fn send(command: &str) -> std::io::Result<String> {
+    use std::io::{BufRead, Write};
+    let mut stream = std::os::unix::net::UnixStream::connect(SOCKET_PATH.as_path())?;
+    writeln!(stream, "{command}")?;
+    let mut reply = String::new();
+    std::io::BufReader::new(stream).read_line(&mut reply)?;
+    Ok(reply.trim_end().to_string())
+}
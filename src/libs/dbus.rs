@@ -0,0 +1,162 @@
+use crate::*;
+use anyhow::Result;
+use gio::{BusType, DBusCallFlags, DBusConnection, DBusSignalFlags, SignalSubscriptionId};
+use glib::{variant::ObjectPath, Variant, VariantDict, VariantTy};
+
+pub async fn system() -> Result<DBusConnection> {
+    Ok(gio::bus_get_future(BusType::System).await?)
+}
+
+pub async fn session() -> Result<DBusConnection> {
+    Ok(gio::bus_get_future(BusType::Session).await?)
+}
+
+pub async fn call(
+    conn: &DBusConnection,
+    dest: &str,
+    path: &str,
+    iface: &str,
+    method: &str,
+    params: Option<&Variant>,
+) -> Result<Variant> {
+    Ok(conn
+        .call_future(
+            Some(dest),
+            path,
+            iface,
+            method,
+            params,
+            None,
+            DBusCallFlags::NONE,
+            -1,
+        )
+        .await?)
+}
+
+/// `org.freedesktop.DBus.Properties.Get` with the outer `v` already unwrapped
+pub async fn property(
+    conn: &DBusConnection,
+    dest: &str,
+    path: &str,
+    iface: &str,
+    name: &str,
+) -> Result<Variant> {
+    let reply = call(
+        conn,
+        dest,
+        path,
+        "org.freedesktop.DBus.Properties",
+        "Get",
+        Some(&(iface, name).to_variant()),
+    )
+    .await?;
+    reply
+        .child_value(0)
+        .as_variant()
+        .ok_or(anyhow::anyhow!("{path} {iface}.{name} is not a variant"))
+}
+
+/// `org.freedesktop.DBus.Properties.GetAll`
+pub async fn properties(
+    conn: &DBusConnection,
+    dest: &str,
+    path: &str,
+    iface: &str,
+) -> Result<VariantDict> {
+    let reply = call(
+        conn,
+        dest,
+        path,
+        "org.freedesktop.DBus.Properties",
+        "GetAll",
+        Some(&(iface,).to_variant()),
+    )
+    .await?;
+    Ok(VariantDict::new(Some(&reply.child_value(0))))
+}
+
+pub async fn set_property(
+    conn: &DBusConnection,
+    dest: &str,
+    path: &str,
+    iface: &str,
+    name: &str,
+    value: Variant,
+) -> Result<()> {
+    call(
+        conn,
+        dest,
+        path,
+        "org.freedesktop.DBus.Properties",
+        "Set",
+        Some(&(iface, name, value).to_variant()),
+    )
+    .await?;
+    Ok(())
+}
+
+/// calls `f` with the object path and the signal's parameters
+pub fn on_signal<F>(
+    conn: &DBusConnection,
+    sender: &str,
+    iface: &str,
+    member: &str,
+    path: Option<&str>,
+    f: F,
+) -> SignalSubscriptionId
+where
+    F: Fn(&str, &Variant) + 'static,
+{
+    conn.signal_subscribe(
+        Some(sender),
+        Some(iface),
+        Some(member),
+        path,
+        None,
+        DBusSignalFlags::NONE,
+        move |_, _, path, _, _, params| f(path, params),
+    )
+}
+
+/// calls `f` with the object path, the interface and the changed properties
+pub fn on_properties_changed<F>(
+    conn: &DBusConnection,
+    sender: &str,
+    path: Option<&str>,
+    f: F,
+) -> SignalSubscriptionId
+where
+    F: Fn(&str, &str, &VariantDict) + 'static,
+{
+    on_signal(
+        conn,
+        sender,
+        "org.freedesktop.DBus.Properties",
+        "PropertiesChanged",
+        path,
+        move |path, params| {
+            if let Some(iface) = params.child_value(0).str() {
+                f(path, iface, &VariantDict::new(Some(&params.child_value(1))));
+            }
+        },
+    )
+}
+
+/// an `o` value, falls back to `/` which services read as "none"
+pub fn object_path(path: &str) -> ObjectPath {
+    ObjectPath::try_from(path)
+        .or_else(|_| ObjectPath::try_from("/"))
+        .unwrap()
+}
+
+/// string-ish values (`s`, `o`, `g`), object path arrays and the like
+pub fn strings(value: &Variant) -> Vec<String> {
+    if value.is_type(VariantTy::STRING_ARRAY) || value.is_type(VariantTy::OBJECT_PATH_ARRAY) {
+        value
+            .iter()
+            .filter_map(|v| v.str().map(str::to_string))
+            .collect()
+    } else {
+        value.str().map(str::to_string).into_iter().collect()
+    }
+}
@@ -0,0 +1,177 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// a variable a module hands to its template
+pub enum Value {
+    Text(String),
+    /// the number and how many decimals it shows when the template doesn't say
+    Number(f64, usize),
+}
+
+impl From<&str> for Value {
+    fn from(text: &str) -> Self {
+        Self::Text(text.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(text: String) -> Self {
+        Self::Text(text)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(number: f64) -> Self {
+        Self::Number(number, 0)
+    }
+}
+
+/// icons picked by percentage, either one list or a list per state like
+/// `{"charging": [..], "default": [..]}`
+#[derive(Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum Icons {
+    List(Vec<String>),
+    States(HashMap<String, Vec<String>>),
+}
+
+impl Icons {
+    /// the list of `state`, or the `default` one, spread over 0 to 100 so the first
+    /// is for 0 and the last for 100, the way custom modules always picked them
+    pub fn pick(&self, state: &str, percent: f64) -> Option<&str> {
+        let icons = match self {
+            Self::List(icons) => icons,
+            Self::States(states) => states.get(state).or_else(|| states.get("default"))?,
+        };
+        let last = icons.len().checked_sub(1)?;
+        let i = (percent.clamp(0.0, 100.0) / 100.0 * last as f64).round() as usize;
+        icons.get(i.min(last)).map(String::as_str)
+    }
+}
+
+/// how a module's label reads, the module keeps its own look for whatever isn't set
+#[derive(Deserialize, Default, Debug, Clone)]
+#[serde(default)]
+pub struct Format {
+    /// `{name}` is replaced with the module's variable, `{name:.2}` picks the decimals
+    pub format: Option<String>,
+    pub icons: Option<Icons>,
+}
+
+impl Format {
+    /// the configured template or `default` filled in with `vars`
+    pub fn render(&self, default: &str, vars: &[(&str, Value)]) -> String {
+        render(self.format.as_deref().unwrap_or(default), vars)
+    }
+    /// the configured icon for `state` at `percent`, none means the module's own
+    pub fn icon(&self, state: &str, percent: f64) -> Option<&str> {
+        self.icons.as_ref()?.pick(state, percent)
+    }
+}
+
+/// fills `{name}` and `{name:.N}` in `template`, `{{` and `}}` are literal braces
+/// and unknown names are left as they are so typos show up on the bar
+pub fn render(template: &str, vars: &[(&str, Value)]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(i) = rest.find(['{', '}']) {
+        out.push_str(&rest[..i]);
+        let brace = &rest[i..];
+        if brace.starts_with("{{") || brace.starts_with("}}") {
+            out.push_str(&brace[..1]);
+            rest = &brace[2..];
+            continue;
+        }
+        let Some(end) = brace.find('}').filter(|_| brace.starts_with('{')) else {
+            out.push_str(&brace[..1]);
+            rest = &brace[1..];
+            continue;
+        };
+        let spec = &brace[1..end];
+        let (name, precision) = match spec.split_once(":.") {
+            Some((name, precision)) => (name, precision.parse::<usize>().ok()),
+            None => (spec, None),
+        };
+        match vars.iter().find(|(n, _)| *n == name) {
+            Some((_, Value::Text(text))) => out.push_str(text),
+            Some((_, Value::Number(number, decimals))) => {
+                let _ = write!(out, "{:.*}", precision.unwrap_or(*decimals), number);
+            }
+            None => out.push_str(&brace[..=end]),
+        }
+        rest = &brace[end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_fills_variables() {
+        let vars = [("name", "wlan0".into()), ("strength", 72.0.into())];
+        assert_eq!(render("{name} {strength}%", &vars), "wlan0 72%");
+    }
+
+    #[test]
+    fn render_precision() {
+        let vars = [("load", Value::Number(0.5234, 2))];
+        assert_eq!(render("{load}", &vars), "0.52");
+        assert_eq!(render("{load:.1}", &vars), "0.5");
+        assert_eq!(render("{load:.0}", &vars), "1");
+    }
+
+    #[test]
+    fn render_escapes_and_unknowns() {
+        let vars = [("text", "a".into())];
+        assert_eq!(render("{{text}} {text}", &vars), "{text} a");
+        assert_eq!(render("{missing} {text}", &vars), "{missing} a");
+        assert_eq!(render("open { and } close", &vars), "open { and } close");
+    }
+
+    #[test]
+    fn format_falls_back_to_default() {
+        let format = Format::default();
+        assert_eq!(format.render("{v}%", &[("v", 5.0.into())]), "5%");
+        let format = Format {
+            format: Some("v={v}".to_string()),
+            icons: None,
+        };
+        assert_eq!(format.render("{v}%", &[("v", 5.0.into())]), "v=5");
+    }
+
+    fn list(icons: &[&str]) -> Icons {
+        Icons::List(icons.iter().map(|i| i.to_string()).collect())
+    }
+
+    #[test]
+    fn pick_spreads_from_first_to_last() {
+        let icons = list(&["a", "b", "c", "d", "e"]);
+        assert_eq!(icons.pick("default", 0.0), Some("a"));
+        assert_eq!(icons.pick("default", 20.0), Some("b"));
+        assert_eq!(icons.pick("default", 50.0), Some("c"));
+        assert_eq!(icons.pick("default", 100.0), Some("e"));
+        assert_eq!(icons.pick("default", 150.0), Some("e"));
+        assert_eq!(icons.pick("default", -10.0), Some("a"));
+    }
+
+    #[test]
+    fn pick_edge_cases() {
+        assert_eq!(list(&[]).pick("default", 50.0), None);
+        assert_eq!(list(&["only"]).pick("default", 100.0), Some("only"));
+        assert_eq!(list(&["a", "b"]).pick("default", f64::NAN), Some("a"));
+    }
+
+    #[test]
+    fn pick_by_state() {
+        let icons: Icons =
+            serde_json::from_str(r#"{"charging": ["c"], "default": ["d0", "d1"]}"#).unwrap();
+        assert_eq!(icons.pick("charging", 100.0), Some("c"));
+        assert_eq!(icons.pick("warning", 100.0), Some("d1"));
+        let icons: Icons = serde_json::from_str(r#"{"charging": ["c"]}"#).unwrap();
+        assert_eq!(icons.pick("warning", 100.0), None);
+    }
+}
@@ -0,0 +1,77 @@
+use crate::*;
+use gtk::{IconTheme, Image};
+use libs::config::IconStyle;
+use std::cell::OnceCell;
+
+thread_local! {
+    static NERD_FONT: OnceCell<bool> = const { OnceCell::new() };
+}
+
+/// whether any installed font carries the nerd font glyphs
+fn nerd_font() -> bool {
+    NERD_FONT.with(|n| {
+        *n.get_or_init(|| {
+            Label::new(None)
+                .pango_context()
+                .list_families()
+                .iter()
+                .any(|f| f.name().contains("Nerd"))
+        })
+    })
+}
+
+/// the style `module` ends up with, `auto` is settled here
+pub fn style(module: &str) -> IconStyle {
+    let style = CONFIG
+        .icons
+        .modules
+        .get(module)
+        .copied()
+        .unwrap_or(CONFIG.icons.style);
+    match style {
+        IconStyle::Auto if nerd_font() => IconStyle::Glyph,
+        IconStyle::Auto => IconStyle::Theme,
+        style => style,
+    }
+}
+
+/// a nerd font glyph or a named icon from the gtk icon theme, whichever the module's style asks for
+#[derive(Clone)]
+pub struct Icon {
+    pub widget: Box,
+    glyph: Label,
+    image: Image,
+    theme: bool,
+}
+
+impl Icon {
+    pub fn new(module: &str) -> Self {
+        let widget = Box::new(Horizontal, 0);
+        let glyph = Label::new(None);
+        let image = Image::new();
+        image.set_visible(false);
+        widget.append(&glyph);
+        widget.append(&image);
+        Self {
+            widget,
+            glyph,
+            image,
+            theme: style(module) == IconStyle::Theme,
+        }
+    }
+    /// whether `name` would be shown as a theme icon rather than the glyph
+    pub fn uses_theme(&self, name: &str) -> bool {
+        self.theme && Display::default().is_some_and(|d| IconTheme::for_display(&d).has_icon(name))
+    }
+    /// shows the theme icon `name`, or `glyph` when glyphs are used or the theme lacks the icon
+    pub fn set(&self, glyph: &str, name: &str) {
+        let theme = self.uses_theme(name);
+        if theme {
+            self.image.set_icon_name(Some(name));
+        } else {
+            self.glyph.set_text(glyph);
+        }
+        self.image.set_visible(theme);
+        self.glyph.set_visible(!theme);
+    }
+}
@@ -1,3 +1,14 @@
+pub mod animation;
+pub mod autohide;
+pub mod config;
+pub mod control;
+pub mod dbus;
+pub mod format;
 pub mod hyprland;
+pub mod icons;
+pub mod notifications;
+pub mod process;
 pub mod pulse;
 pub mod shared_widget;
+pub mod signals;
+pub mod tooltip;
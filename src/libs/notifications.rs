@@ -0,0 +1,365 @@
+use crate::*;
+use async_broadcast::{broadcast, InactiveReceiver, Sender};
+use chrono::{DateTime, Local};
+use gio::{BusNameOwnerFlags, BusType, DBusConnection, DBusMethodInvocation, DBusNodeInfo};
+use glib::Variant;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+const NAME: &str = "org.freedesktop.Notifications";
+const PATH: &str = "/org/freedesktop/Notifications";
+
+const INTROSPECTION: &str = r#"
+<node>
+  <interface name="org.freedesktop.Notifications">
+    <method name="GetCapabilities">
+      <arg direction="out" name="capabilities" type="as"/>
+    </method>
+    <method name="Notify">
+      <arg direction="in" name="app_name" type="s"/>
+      <arg direction="in" name="replaces_id" type="u"/>
+      <arg direction="in" name="app_icon" type="s"/>
+      <arg direction="in" name="summary" type="s"/>
+      <arg direction="in" name="body" type="s"/>
+      <arg direction="in" name="actions" type="as"/>
+      <arg direction="in" name="hints" type="a{sv}"/>
+      <arg direction="in" name="expire_timeout" type="i"/>
+      <arg direction="out" name="id" type="u"/>
+    </method>
+    <method name="CloseNotification">
+      <arg direction="in" name="id" type="u"/>
+    </method>
+    <method name="GetServerInformation">
+      <arg direction="out" name="name" type="s"/>
+      <arg direction="out" name="vendor" type="s"/>
+      <arg direction="out" name="version" type="s"/>
+      <arg direction="out" name="spec_version" type="s"/>
+    </method>
+    <signal name="NotificationClosed">
+      <arg name="id" type="u"/>
+      <arg name="reason" type="u"/>
+    </signal>
+    <signal name="ActionInvoked">
+      <arg name="id" type="u"/>
+      <arg name="action_key" type="s"/>
+    </signal>
+  </interface>
+</node>
+"#;
+
+/// the arguments of `Notify`, `susssasa{sv}i`
+type NotifyArgs = (
+    String,
+    u32,
+    String,
+    String,
+    String,
+    Vec<String>,
+    HashMap<String, Variant>,
+    i32,
+);
+
+#[derive(Clone, Copy, PartialEq, PartialOrd)]
+pub enum Urgency {
+    Low,
+    Normal,
+    Critical,
+}
+
+/// why a notification went away, as the spec numbers them
+#[derive(Clone, Copy)]
+pub enum Reason {
+    Expired = 1,
+    Dismissed = 2,
+    Closed = 3,
+    /// pushed out of a full history
+    Undefined = 4,
+}
+
+#[derive(Clone)]
+pub struct Notification {
+    pub id: u32,
+    pub app_name: String,
+    /// an icon name or a path
+    pub icon: String,
+    pub summary: String,
+    pub body: String,
+    /// action key and its label
+    pub actions: Vec<(String, String)>,
+    pub urgency: Urgency,
+    /// milliseconds, none for notifications that stay until they're dismissed
+    pub timeout: Option<u32>,
+    pub time: DateTime<Local>,
+    /// bumped when the notification is replaced so older expiry timers give up
+    revision: u64,
+}
+
+#[derive(Clone)]
+pub enum Event {
+    /// a new notification or a replacement for one with the same id
+    Notified(Notification),
+    /// the popup for this id should go away
+    Closed(u32),
+    /// the history, the unread count or do not disturb changed
+    Changed,
+}
+
+#[derive(Default)]
+struct State {
+    next_id: u32,
+    revision: u64,
+    /// newest first
+    history: Vec<Notification>,
+    /// ids that still have a popup
+    active: Vec<u32>,
+    unread: usize,
+    dnd: bool,
+}
+
+#[derive(Clone)]
+pub struct Daemon {
+    state: Rc<RefCell<State>>,
+    conn: Rc<RefCell<Option<DBusConnection>>>,
+    sender: Sender<Event>,
+    receiver: InactiveReceiver<Event>,
+}
+
+impl Daemon {
+    /// owns `org.freedesktop.Notifications` on the session bus
+    pub fn start() -> Self {
+        let (mut sender, receiver) = broadcast(64);
+        sender.set_overflow(true);
+        let this = Self {
+            state: Rc::new(RefCell::new(State {
+                next_id: 1,
+                dnd: CONFIG.notifications.dnd,
+                ..Default::default()
+            })),
+            conn: Rc::new(RefCell::new(None)),
+            sender,
+            receiver: receiver.deactivate(),
+        };
+        gio::bus_own_name(
+            BusType::Session,
+            NAME,
+            BusNameOwnerFlags::NONE,
+            clone! {
+                #[strong] this,
+                move |conn, _| this.register(conn)
+            },
+            |_, _| {},
+            |_, _| eprintln!("notifications: {NAME} is owned by another daemon"),
+        );
+        this
+    }
+    fn register(&self, conn: DBusConnection) {
+        let info = DBusNodeInfo::for_xml(INTROSPECTION)
+            .ok()
+            .and_then(|node| node.lookup_interface(NAME))
+            .expect("notification introspection is valid");
+        let result = conn
+            .register_object(PATH, &info)
+            .method_call(clone! {
+                #[strong(rename_to = this)] self,
+                move |_, _, _, _, method, params, invocation| {
+                    this.method_call(method, params, invocation)
+                }
+            })
+            .build();
+        if let Err(e) = result {
+            eprintln!("notifications: {e}");
+        }
+        self.conn.replace(Some(conn));
+    }
+    fn method_call(&self, method: &str, params: Variant, invocation: DBusMethodInvocation) {
+        match method {
+            "GetCapabilities" => {
+                let capabilities = vec!["actions", "body", "body-markup", "persistence"];
+                invocation.return_value(Some(&(capabilities,).to_variant()));
+            }
+            "GetServerInformation" => {
+                let info = ("gtk-bar", "z3phyrl", env!("CARGO_PKG_VERSION"), "1.2");
+                invocation.return_value(Some(&info.to_variant()));
+            }
+            "Notify" => {
+                let Some(args) = params.get::<NotifyArgs>() else {
+                    invocation.return_dbus_error(
+                        "org.freedesktop.DBus.Error.InvalidArgs",
+                        "unexpected arguments",
+                    );
+                    return;
+                };
+                let id = self.notify(args);
+                invocation.return_value(Some(&(id,).to_variant()));
+            }
+            "CloseNotification" => {
+                if let Some((id,)) = params.get::<(u32,)>() {
+                    self.close_notification(id);
+                }
+                invocation.return_value(None);
+            }
+            _ => invocation.return_dbus_error(
+                "org.freedesktop.DBus.Error.UnknownMethod",
+                &format!("{method} isn't supported"),
+            ),
+        }
+    }
+    fn notify(&self, args: NotifyArgs) -> u32 {
+        let (app_name, replaces_id, icon, summary, body, actions, hints, timeout) = args;
+        let urgency = match hints.get("urgency").and_then(|u| u.get::<u8>()) {
+            Some(0) => Urgency::Low,
+            Some(2) => Urgency::Critical,
+            _ => Urgency::Normal,
+        };
+        let icon = match hints.get("image-path").and_then(|p| p.str()) {
+            Some(path) => path.to_string(),
+            None => icon,
+        };
+        let timeout = match timeout {
+            // critical notifications stay unless the sender says otherwise
+            -1 if urgency == Urgency::Critical => None,
+            -1 => Some(CONFIG.notifications.timeout),
+            0 => None,
+            t => Some(t.max(0) as u32),
+        };
+        let mut state = self.state.borrow_mut();
+        state.revision += 1;
+        let replaces = state.history.iter().position(|n| n.id == replaces_id);
+        let id = if replaces.is_some() {
+            replaces_id
+        } else {
+            let id = state.next_id;
+            state.next_id = state.next_id.wrapping_add(1).max(1);
+            id
+        };
+        let notification = Notification {
+            id,
+            app_name,
+            icon,
+            summary,
+            body,
+            // flat list of key, label pairs
+            actions: actions
+                .chunks_exact(2)
+                .map(|a| (a[0].clone(), a[1].clone()))
+                .collect(),
+            urgency,
+            timeout,
+            time: Local::now(),
+            revision: state.revision,
+        };
+        let mut evicted = Vec::new();
+        match replaces {
+            Some(i) => state.history[i] = notification.clone(),
+            None => {
+                state.history.insert(0, notification.clone());
+                if state.history.len() > CONFIG.notifications.history {
+                    evicted = state.history.split_off(CONFIG.notifications.history);
+                }
+                state.unread += 1;
+            }
+        }
+        // while do not disturb is on only critical ones pop up
+        let popup = !state.dnd || urgency == Urgency::Critical;
+        if popup && !state.active.contains(&id) {
+            state.active.push(id);
+        }
+        drop(state);
+        // their expiry timers look them up in the history, so their popups go now
+        for old in evicted {
+            self.close(old.id, Reason::Undefined);
+        }
+        if popup {
+            let _ = self.sender.try_broadcast(Event::Notified(notification.clone()));
+            libs::autohide::request_attention();
+        }
+        let _ = self.sender.try_broadcast(Event::Changed);
+        if let (true, Some(timeout)) = (popup, timeout) {
+            spawn_future_local(clone! {
+                #[strong(rename_to = this)] self,
+                async move {
+                    sleep(Duration::from_millis(timeout as u64)).await;
+                    let current = this.state.borrow().history.iter().any(|n| {
+                        n.id == notification.id && n.revision == notification.revision
+                    });
+                    if current {
+                        this.close(notification.id, Reason::Expired);
+                    }
+                }
+            });
+        }
+        id
+    }
+    fn emit(&self, signal: &str, params: Variant) {
+        if let Some(conn) = self.conn.borrow().as_ref() {
+            if let Err(e) = conn.emit_signal(None, PATH, NAME, signal, Some(&params)) {
+                eprintln!("notifications: {signal}: {e}");
+            }
+        }
+    }
+    pub fn listener(&self) -> async_broadcast::Receiver<Event> {
+        self.receiver.activate_cloned()
+    }
+    /// takes the popup down, the notification stays in the history
+    pub fn close(&self, id: u32, reason: Reason) {
+        let mut state = self.state.borrow_mut();
+        let Some(i) = state.active.iter().position(|a| *a == id) else {
+            return;
+        };
+        state.active.remove(i);
+        drop(state);
+        self.emit("NotificationClosed", (id, reason as u32).to_variant());
+        let _ = self.sender.try_broadcast(Event::Closed(id));
+    }
+    /// `CloseNotification` from the sender, which hears back for anything still in the history
+    /// even when its popup is already gone
+    fn close_notification(&self, id: u32) {
+        let state = self.state.borrow();
+        let active = state.active.contains(&id);
+        let known = active || state.history.iter().any(|n| n.id == id);
+        drop(state);
+        if active {
+            self.close(id, Reason::Closed);
+        } else if known {
+            self.emit("NotificationClosed", (id, Reason::Closed as u32).to_variant());
+        }
+        if known {
+            self.remove(id);
+        }
+    }
+    /// drops a notification from the history
+    pub fn remove(&self, id: u32) {
+        self.close(id, Reason::Dismissed);
+        self.state.borrow_mut().history.retain(|n| n.id != id);
+        let _ = self.sender.try_broadcast(Event::Changed);
+    }
+    pub fn clear(&self) {
+        let ids: Vec<u32> = self.state.borrow().history.iter().map(|n| n.id).collect();
+        for id in ids {
+            self.close(id, Reason::Dismissed);
+        }
+        self.state.borrow_mut().history.clear();
+        let _ = self.sender.try_broadcast(Event::Changed);
+    }
+    pub fn invoke(&self, id: u32, action: &str) {
+        self.emit("ActionInvoked", (id, action).to_variant());
+        self.close(id, Reason::Dismissed);
+    }
+    pub fn history(&self) -> Vec<Notification> {
+        self.state.borrow().history.clone()
+    }
+    pub fn unread(&self) -> usize {
+        self.state.borrow().unread
+    }
+    pub fn mark_read(&self) {
+        self.state.borrow_mut().unread = 0;
+        let _ = self.sender.try_broadcast(Event::Changed);
+    }
+    pub fn dnd(&self) -> bool {
+        self.state.borrow().dnd
+    }
+    pub fn set_dnd(&self, dnd: bool) {
+        self.state.borrow_mut().dnd = dnd;
+        let _ = self.sender.try_broadcast(Event::Changed);
+    }
+}
@@ -0,0 +1,41 @@
+use crate::*;
+use gio::{Subprocess, SubprocessFlags};
+use std::ffi::OsStr;
+
+fn start(argv: &[&str], flags: SubprocessFlags) -> Result<Subprocess, glib::Error> {
+    let argv: Vec<&OsStr> = argv.iter().map(OsStr::new).collect();
+    Subprocess::newv(&argv, flags)
+}
+
+/// starts `argv` and forgets about it, glib reaps it once it exits
+pub fn spawn(argv: &[&str]) -> Result<(), glib::Error> {
+    start(argv, SubprocessFlags::STDOUT_SILENCE).map(|_| ())
+}
+
+/// `spawn` for a line of shell
+pub fn sh(command: &str) -> Result<(), glib::Error> {
+    spawn(&["sh", "-c", command])
+}
+
+/// runs `argv` to the end without blocking the main loop and hands back what it printed
+pub async fn output(argv: &[&str]) -> Result<String, glib::Error> {
+    let process = start(
+        argv,
+        SubprocessFlags::STDOUT_PIPE | SubprocessFlags::STDERR_SILENCE,
+    )?;
+    let (stdout, _) = process.communicate_future(None).await?;
+    Ok(stdout.map_or_else(String::new, |s| String::from_utf8_lossy(&s).to_string()))
+}
+
+/// keeps `argv` running and calls `line` for everything it prints, returns once it exits
+pub async fn lines<F: Fn(&str)>(argv: &[&str], line: F) -> Result<(), glib::Error> {
+    let process = start(argv, SubprocessFlags::STDOUT_PIPE)?;
+    let Some(stdout) = process.stdout_pipe() else {
+        return Ok(());
+    };
+    let stdout = gio::DataInputStream::new(&stdout);
+    while let Ok(Some(text)) = stdout.read_line_utf8_future(glib::Priority::DEFAULT).await {
+        line(&text);
+    }
+    process.wait_future().await
+}
@@ -120,6 +120,19 @@ impl Pulse {
             protocol::read_reply_message::<protocol::SinkInfo>(&mut sock, self.version)?;
         Ok(reply)
     }
+    /// what's currently recording, corked streams included
+    pub fn get_source_outputs(&self) -> Result<Vec<protocol::SourceOutputInfo>> {
+        let mut sock = BufReader::new(self.sock.borrow().try_clone()?);
+        protocol::write_command_message(
+            sock.get_mut(),
+            0,
+            protocol::Command::GetSourceOutputInfoList,
+            self.version,
+        )?;
+        let (_, reply) =
+            protocol::read_reply_message::<protocol::SourceOutputInfoList>(&mut sock, self.version)?;
+        Ok(reply)
+    }
     pub fn set_sink_mute(&self, index: u32, mute: bool) -> Result<()> {
         let mut sock = self.sock.borrow().try_clone()?;
         Ok(protocol::write_command_message(
@@ -1,61 +1,82 @@
 use crate::*;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
 
+pub fn spacer(space: i32) -> Box {
+    if CONFIG.bar.position.vertical() {
+        Box::builder().margin_top(space / 2).margin_bottom(space / 2).build()
+    } else {
+        Box::builder().margin_start(space / 2).margin_end(space / 2).build()
+    }
+}
+
+/// the direction modules are laid out in, along the edge the bar is on
+pub fn orientation() -> Orientation {
+    if CONFIG.bar.position.vertical() {
+        Vertical
+    } else {
+        Horizontal
+    }
+}
+
+/// a tiny line graph of the last few values, scaled to the largest one unless it has a fixed maximum
 #[derive(Clone)]
-pub struct CrossfadeIn {
-    pub widget: Revealer,
-    slidein: Revealer,
-    crossfade: Revealer,
-    duration: Duration,
+pub struct Sparkline {
+    pub widget: gtk::DrawingArea,
+    history: Rc<RefCell<VecDeque<f64>>>,
+    len: usize,
 }
 
-impl CrossfadeIn {
-    pub fn new<W>(child: &W, duration: Duration) -> Self
-    where
-        W: IsA<Widget>,
-    {
-        let crossfade = Revealer::builder()
-            .transition_type(Crossfade)
-            .transition_duration(duration.as_millis() as u32)
-            .child(child)
-            .build();
-        let slidein = Revealer::builder()
-            .transition_type(SlideRight)
-            .transition_duration(duration.as_millis() as u32 / 2)
-            .child(&crossfade)
+impl Sparkline {
+    pub fn new(len: usize) -> Self {
+        Self::with_max(len, None)
+    }
+    pub fn with_max(len: usize, max: Option<f64>) -> Self {
+        let widget = gtk::DrawingArea::builder()
+            .content_width(len as i32 * 2)
+            .css_classes(["sparkline"])
             .build();
+        let history = Rc::new(RefCell::new(VecDeque::from(vec![0.0; len])));
+        widget.set_draw_func(clone! {
+            #[strong] history,
+            move |area, cr, width, height| {
+                let history = history.borrow();
+                let max =
+                    max.unwrap_or_else(|| history.iter().cloned().fold(f64::EPSILON, f64::max));
+                let step = width as f64 / (history.len().max(2) - 1) as f64;
+                let color = area.color();
+                cr.set_source_rgba(
+                    color.red() as f64,
+                    color.green() as f64,
+                    color.blue() as f64,
+                    color.alpha() as f64,
+                );
+                cr.set_line_width(1.5);
+                for (i, value) in history.iter().enumerate() {
+                    let y = height as f64 - value / max * (height as f64 - 2.0) - 1.0;
+                    if i == 0 {
+                        cr.move_to(0.0, y);
+                    } else {
+                        cr.line_to(i as f64 * step, y);
+                    }
+                }
+                let _ = cr.stroke();
+            }
+        });
         Self {
-            widget: slidein.clone(),
-            slidein,
-            crossfade,
-            duration,
+            widget,
+            history,
+            len,
         }
     }
-    pub fn reveal(&self, reveal: bool) {
-        if reveal {
-            spawn_future_local(clone! {
-                #[strong(rename_to = this)] self,
-                async move {
-                    this.slidein.set_reveal_child(reveal);
-                    sleep(this.duration / 6).await;
-                    this.crossfade.set_reveal_child(reveal);
-                }
-            });
-        } else {
-            spawn_future_local(clone! {
-                #[strong(rename_to = this)] self,
-                async move {
-                    this.crossfade.set_reveal_child(reveal);
-                    sleep(this.duration / 2).await;
-                    this.slidein.set_reveal_child(reveal);
-                }
-            });
+    pub fn push(&self, value: f64) {
+        let mut history = self.history.borrow_mut();
+        history.push_back(value);
+        while history.len() > self.len {
+            history.pop_front();
         }
+        drop(history);
+        self.widget.queue_draw();
     }
-    pub fn revealed(&self) -> bool {
-        self.slidein.reveals_child() || self.crossfade.reveals_child()
-    }
-}
-
-pub fn spacer(space: i32) -> Box {
-    Box::builder().margin_start(space / 2).margin_end(space / 2).build()
 }
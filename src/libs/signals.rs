@@ -0,0 +1,75 @@
+use crate::*;
+use async_std::os::unix::net::UnixStream;
+use std::cell::RefCell;
+use std::os::fd::IntoRawFd;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicI32, Ordering};
+
+/// write end of the pipe the signal handler pokes, -1 until the first handler is installed
+static WAKE_FD: AtomicI32 = AtomicI32::new(-1);
+
+type Handlers = HashMap<i32, Vec<Rc<dyn Fn()>>>;
+
+thread_local! {
+    static HANDLERS: RefCell<Handlers> = RefCell::new(HashMap::new());
+}
+
+// only async-signal-safe things in here, the signal number goes down the pipe
+extern "C" fn on_signal(signal: libc::c_int) {
+    let fd = WAKE_FD.load(Ordering::Relaxed);
+    let byte = signal as u8;
+    unsafe {
+        libc::write(fd, &byte as *const u8 as *const libc::c_void, 1);
+    }
+}
+
+fn start() -> std::io::Result<()> {
+    let (read, write) = std::os::unix::net::UnixStream::pair()?;
+    write.set_nonblocking(true)?;
+    WAKE_FD.store(write.into_raw_fd(), Ordering::Relaxed);
+    let mut read = UnixStream::from(read);
+    spawn_future_local(async move {
+        let mut buf = [0u8; 16];
+        while let Ok(n) = read.read(&mut buf).await {
+            if n == 0 {
+                break;
+            }
+            for signal in &buf[..n] {
+                let handlers = HANDLERS.with(|h| h.borrow().get(&(*signal as i32)).cloned());
+                for handler in handlers.unwrap_or_default() {
+                    handler();
+                }
+            }
+        }
+    });
+    Ok(())
+}
+
+/// calls `f` on the main loop whenever `SIGRTMIN+offset` arrives, like `pkill -RTMIN+1 gtk-bar`
+pub fn on_rt_signal<F>(offset: i32, f: F)
+where
+    F: Fn() + 'static,
+{
+    let signal = libc::SIGRTMIN() + offset;
+    if offset < 0 || signal > libc::SIGRTMAX() {
+        eprintln!("signals: RTMIN+{offset} is out of range");
+        return;
+    }
+    if WAKE_FD.load(Ordering::Relaxed) < 0 {
+        if let Err(e) = start() {
+            eprintln!("signals: {e}");
+            return;
+        }
+    }
+    let fresh = HANDLERS.with(|h| {
+        let mut handlers = h.borrow_mut();
+        let list = handlers.entry(signal).or_default();
+        list.push(Rc::new(f));
+        list.len() == 1
+    });
+    if fresh {
+        unsafe {
+            libc::signal(signal, on_signal as libc::sighandler_t);
+        }
+    }
+}
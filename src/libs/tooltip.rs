@@ -0,0 +1,72 @@
+use crate::*;
+
+/// markup tooltip that's built right before gtk shows it, so it's never stale
+/// and nothing is computed while nobody is hovering
+pub fn lazy<W, F>(widget: &W, f: F)
+where
+    W: IsA<Widget>,
+    F: Fn() -> Option<String> + 'static,
+{
+    if !CONFIG.tooltips.enabled {
+        return;
+    }
+    widget.set_has_tooltip(true);
+    widget.connect_query_tooltip(move |_, _, _, _, tooltip| match f() {
+        Some(markup) if !markup.is_empty() => {
+            if gtk::pango::parse_markup(&markup, '\0').is_ok() {
+                tooltip.set_markup(Some(&markup));
+            } else {
+                tooltip.set_text(Some(&markup));
+            }
+            true
+        }
+        _ => false,
+    });
+}
+
+/// sets a markup tooltip right away, for modules that already have the text on hand
+pub fn set<W>(widget: &W, markup: &str)
+where
+    W: IsA<Widget>,
+{
+    if CONFIG.tooltips.enabled {
+        widget.set_tooltip_markup(Some(markup).filter(|m| !m.is_empty()));
+    }
+}
+
+pub fn escape(text: &str) -> String {
+    glib::markup_escape_text(text).to_string()
+}
+
+/// one `key  value` line with the key in bold, the value is escaped
+pub fn row(key: &str, value: &str) -> String {
+    format!("<b>{}</b>  {}", escape(key), escape(value))
+}
+
+/// same as `set` for plain text that shouldn't be read as markup
+pub fn text<W>(widget: &W, text: &str)
+where
+    W: IsA<Widget>,
+{
+    if CONFIG.tooltips.enabled {
+        widget.set_tooltip_text(Some(text).filter(|t| !t.is_empty()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_markup() {
+        assert_eq!(escape("a < b & c > d"), "a &lt; b &amp; c &gt; d");
+        assert_eq!(escape("it's \"quoted\""), "it&#39;s &quot;quoted&quot;");
+        assert_eq!(escape("plain"), "plain");
+    }
+
+    #[test]
+    fn row_escapes_both_sides() {
+        assert_eq!(row("<ssid>", "50%"), "<b>&lt;ssid&gt;</b>  50%");
+        assert_eq!(row("a&b", "<i>"), "<b>a&amp;b</b>  &lt;i&gt;");
+    }
+}
@@ -1,3 +1,4 @@
+use async_broadcast::Receiver;
 use async_std::prelude::*;
 use async_std::task::sleep;
 use chrono::Local;
@@ -11,12 +12,12 @@ use gtk::{
         spawn_future_local, timeout_add_local, ControlFlow,
     },
     Application, ApplicationWindow, Box, Button, CenterBox, CssProvider, EventControllerMotion,
-    EventControllerScroll, EventControllerScrollFlags, GestureClick, Label, Orientation::{Vertical, Horizontal}, Overlay,
+    EventControllerScroll, EventControllerScrollFlags, GestureClick, Label, Orientation::{self, Vertical, Horizontal}, Overlay,
     Revealer, RevealerTransitionType::{Crossfade, SlideLeft, SlideRight}, Scale, Widget,
 };
 use gtk4 as gtk;
 use gtk4_layer_shell as layer_shell;
-use layer_shell::{Edge, Layer, LayerShell};
+use layer_shell::{Edge, KeyboardMode, Layer, LayerShell};
 use sass_rs::{compile_string, Options};
 use std::sync::{Arc, Mutex, RwLock};
 use std::time::Duration;
@@ -28,61 +29,166 @@ use serde_json::from_str;
 mod libs;
 mod widgets;
 mod windows;
+use libs::autohide::AutoHide;
+use libs::config::{BarLayer, KeyboardInteractivity, Position, Side, CONFIG};
 use libs::hyprland;
 use libs::shared_widget::spacer;
+use windows::popup_notifications::PopUpNotifications;
 use widgets::{
-    battery, clock,
+    backlight, battery, bluetooth, clock, custom, disk, idle, keyboard,
     root::{self, Root},
-    systray, volume, workspaces, music,
+    network, notifications, power, power_profiles, privacy, submap, sysmon, systray, temperature, throughput, volume, workspaces, music,
 };
 
 fn build_ui(app: &Application) {
     let mut hyprland = hyprland::new();
+    libs::control::listen();
     let mut root = root::new(hyprland.listener());
     root.left.set_spacing(5);
     root.center.set_spacing(15);
     root.right.set_spacing(15);
     root.left(&spacer(15));
     root.left(&workspaces::new(hyprland.listener()));
+    root.left(&submap::new(hyprland.listener()));
     root.left(&music::new());
 
     // root.center();
 
+    if CONFIG.privacy.enabled {
+        root.right(&privacy::new(hyprland.listener()));
+    }
+    if CONFIG.cpu.enabled {
+        root.right(&sysmon::cpu());
+    }
+    if CONFIG.memory.enabled {
+        root.right(&sysmon::memory());
+    }
+    if CONFIG.load.enabled {
+        root.right(&sysmon::load());
+    }
+    if CONFIG.disk.enabled {
+        for mount in &CONFIG.disk.mounts {
+            root.right(&disk::new(mount));
+        }
+    }
+    if CONFIG.temperature.enabled {
+        root.right(&temperature::new());
+    }
+    if CONFIG.throughput.enabled {
+        root.right(&throughput::new());
+    }
+    root.right(&network::new());
+    root.right(&bluetooth::new());
+    root.right(&keyboard::new(hyprland.listener()));
     root.right(&volume::new(app));
+    if let Some(backlight) = backlight::new(app) {
+        root.right(&backlight);
+    }
+    if CONFIG.idle.enabled {
+        root.right(&idle::new());
+    }
+    if CONFIG.notifications.enabled {
+        let daemon = libs::notifications::Daemon::start();
+        PopUpNotifications::new(app, &daemon);
+        root.right(&notifications::new(&daemon));
+    }
     root.right(&systray::new(root.listen()));
     root.right(&clock::new());
     if let Some(batt) = battery::new() {
         root.right(&batt.widget);
     }
+    if CONFIG.power_profiles.enabled {
+        root.right(&power_profiles::new());
+    }
+    if CONFIG.power.enabled {
+        root.right(&power::new());
+    }
+    for (index, config) in CONFIG.custom.iter().enumerate() {
+        let module = custom::new(index, config);
+        match config.side {
+            Side::Left => root.left(&module),
+            Side::Center => root.center(&module),
+            Side::Right => root.right(&module),
+        }
+    }
     root.right(&spacer(0));
 
-    window(app, &root);
+    window(app, &root, hyprland.listener());
     async_std::task::spawn(async move {
         hyprland.listen().await;
     });
 }
 
-fn window(app: &Application, root: &Root) {
+fn monitor(connector: &str) -> Option<gdk::Monitor> {
+    Display::default()?
+        .monitors()
+        .iter::<gdk::Monitor>()
+        .flatten()
+        .find(|m| m.connector().is_some_and(|c| c.as_str() == connector))
+}
+
+fn window(app: &Application, root: &Root, event_listener: Receiver<String>) {
+    let bar = &CONFIG.bar;
+    let (width, height) = if bar.position.vertical() {
+        (bar.height, bar.width)
+    } else {
+        (bar.width, bar.height)
+    };
     let window = ApplicationWindow::builder()
         .application(app)
-        .css_classes(["bar"])
-        .default_width(1920)
-        .default_height(50)
+        .css_classes(["bar", bar.position.css_class()])
+        .default_width(width)
+        .default_height(height)
         .child(&root.widget())
         .build();
 
     window.init_layer_shell();
-    window.auto_exclusive_zone_enable();
-    window.set_layer(Layer::Top);
-    window.set_anchor(Edge::Bottom, true);
+    if bar.exclusive_zone {
+        window.auto_exclusive_zone_enable();
+    }
+    window.set_layer(match bar.layer {
+        BarLayer::Background => Layer::Background,
+        BarLayer::Bottom => Layer::Bottom,
+        BarLayer::Top => Layer::Top,
+        BarLayer::Overlay => Layer::Overlay,
+    });
+    window.set_keyboard_mode(match bar.keyboard_interactivity {
+        KeyboardInteractivity::None => KeyboardMode::None,
+        KeyboardInteractivity::Exclusive => KeyboardMode::Exclusive,
+        KeyboardInteractivity::OnDemand => KeyboardMode::OnDemand,
+    });
+    window.set_anchor(
+        match bar.position {
+            Position::Top => Edge::Top,
+            Position::Bottom => Edge::Bottom,
+            Position::Left => Edge::Left,
+            Position::Right => Edge::Right,
+        },
+        true,
+    );
+    window.set_margin(Edge::Top, bar.margins.top);
+    window.set_margin(Edge::Bottom, bar.margins.bottom);
+    window.set_margin(Edge::Left, bar.margins.left);
+    window.set_margin(Edge::Right, bar.margins.right);
+    if let Some(monitor) = CONFIG.monitor.as_deref().and_then(monitor) {
+        window.set_monitor(&monitor);
+    }
+    if CONFIG.autohide.enabled {
+        AutoHide::new(&window, event_listener);
+    }
 
     window.present();
 }
 
 fn load_css() {
     let provider = CssProvider::new();
-    let css = compile_string(include_str!("style.scss"), Options::default())
-        .expect("Error compileing scss");
+    // the stylesheet sizes things relative to the bar
+    let scss = format!(
+        "$bar-height: {}px;\n{}",
+        CONFIG.bar.height,
+        include_str!("style.scss")
+    );
+    let css = compile_string(&scss, Options::default()).expect("Error compileing scss");
     provider.load_from_string(&css);
 
     gtk::style_context_add_provider_for_display(
@@ -94,6 +200,19 @@ fn load_css() {
 
 #[tokio::main]
 async fn main() -> glib::ExitCode {
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("msg") {
+        return match libs::control::send(&args[2..].join(" ")) {
+            Ok(reply) => {
+                println!("{reply}");
+                glib::ExitCode::SUCCESS
+            }
+            Err(e) => {
+                eprintln!("{}: {e}", libs::control::SOCKET_PATH.display());
+                glib::ExitCode::FAILURE
+            }
+        };
+    }
     let app = Application::builder().application_id("bar").build();
 
     app.connect_startup(|_| load_css());
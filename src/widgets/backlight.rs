@@ -0,0 +1,185 @@
+use crate::*;
+use anyhow::Result;
+use gio::{DBusConnection, FileMonitor, FileMonitorFlags};
+use libs::icons::Icon;
+use libs::{dbus, tooltip};
+use std::path::{Path, PathBuf};
+use windows::popup_osd::PopUpOsd;
+
+#[derive(Clone)]
+struct Device {
+    name: String,
+    path: PathBuf,
+    max: u32,
+}
+
+impl Device {
+    /// the configured device, otherwise the first one found
+    fn find() -> Option<Self> {
+        let name = match &CONFIG.backlight.device {
+            Some(name) => name.clone(),
+            None => {
+                let mut devices: Vec<String> = std::fs::read_dir("/sys/class/backlight")
+                    .ok()?
+                    .flatten()
+                    .map(|d| d.file_name().to_string_lossy().to_string())
+                    .collect();
+                devices.sort();
+                devices.into_iter().next()?
+            }
+        };
+        let path = PathBuf::from("/sys/class/backlight").join(&name);
+        let max = read(&path.join("max_brightness"))?;
+        Some(Self { name, path, max })
+    }
+    fn brightness(&self) -> u32 {
+        read(&self.path.join("actual_brightness"))
+            .or_else(|| read(&self.path.join("brightness")))
+            .unwrap_or(0)
+    }
+    fn percent(&self) -> f64 {
+        self.brightness() as f64 / self.max.max(1) as f64 * 100.0
+    }
+    /// goes through logind so the bar doesn't need write access to sysfs
+    async fn set_percent(&self, conn: &DBusConnection, percent: f64) -> Result<()> {
+        let value = (percent.clamp(0.0, 100.0) / 100.0 * self.max as f64).round() as u32;
+        dbus::call(
+            conn,
+            "org.freedesktop.login1",
+            "/org/freedesktop/login1/session/auto",
+            "org.freedesktop.login1.Session",
+            "SetBrightness",
+            Some(&("backlight", self.name.as_str(), value).to_variant()),
+        )
+        .await?;
+        Ok(())
+    }
+}
+
+fn read(path: &Path) -> Option<u32> {
+    std::fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+fn icon(percent: f64) -> &'static str {
+    let icons = ["󰃞", "󰃟", "󰃝", "󰃠"];
+    CONFIG
+        .backlight
+        .label
+        .icon("default", percent)
+        .unwrap_or(icons[((percent / 100.0 * 3.0).round() as usize).min(3)])
+}
+
+/// the theme icon, configured icons are glyphs or icon names, whichever the style uses
+fn icon_name(percent: f64) -> &'static str {
+    CONFIG
+        .backlight
+        .label
+        .icon("default", percent)
+        .unwrap_or("display-brightness-symbolic")
+}
+
+/// none when there's no backlight to control
+pub fn new(app: &Application) -> Option<Box> {
+    let device = Device::find()?;
+    let widget = Box::new(Horizontal, 5);
+    widget.add_css_class("container");
+    widget.add_css_class("backlight");
+    let icon_widget = Icon::new("backlight");
+    let label = Label::new(None);
+    widget.append(&icon_widget.widget);
+    widget.append(&label);
+
+    let set = clone! {
+        #[strong] device,
+        move |percent: f64| {
+            spawn_future_local(clone! {
+                #[strong] device,
+                async move {
+                    let result = match dbus::system().await {
+                        Ok(conn) => device.set_percent(&conn, percent).await,
+                        Err(e) => Err(e),
+                    };
+                    if let Err(e) = result {
+                        eprintln!("backlight: {e}");
+                    }
+                }
+            });
+        }
+    };
+    let popup = PopUpOsd::new(app, "brightness", "backlight", set.clone(), |percent| {
+        (icon(percent).to_string(), icon_name(percent))
+    });
+    let hover = EventControllerMotion::new();
+    let scroll = EventControllerScroll::new(EventControllerScrollFlags::VERTICAL);
+    scroll.connect_scroll(clone! {
+        #[strong] device,
+        move |_, _, dy| {
+            let step = CONFIG.backlight.step;
+            if dy < -0.5 {
+                set(device.percent() + step);
+            } else if dy > 0.5 {
+                set(device.percent() - step);
+            }
+            Propagation::Stop
+        }
+    });
+    widget.add_controller(scroll);
+    widget.add_controller(hover.clone());
+    tooltip::lazy(
+        &widget,
+        clone! {
+            #[strong] device,
+            move || {
+                Some(format!(
+                    "{}\n{}",
+                    tooltip::row("brightness", &format!("{:.0}%", device.percent())),
+                    tooltip::row("device", &device.name),
+                ))
+            }
+        },
+    );
+
+    let update = clone! {
+        #[strong] device,
+        move || {
+            let percent = device.percent();
+            icon_widget.set(icon(percent), icon_name(percent));
+            label.set_text(&CONFIG.backlight.label.render(
+                "{percent}%",
+                &[("percent", percent.into()), ("icon", icon(percent).into())],
+            ));
+            percent
+        }
+    };
+    update();
+    // sysfs only tells inotify about writes to the file, which is how logind changes it
+    let monitors: Vec<FileMonitor> = ["brightness", "actual_brightness"]
+        .iter()
+        .filter_map(|file| {
+            gio::File::for_path(device.path.join(file))
+                .monitor_file(FileMonitorFlags::NONE, None::<&gio::Cancellable>)
+                .ok()
+        })
+        .collect();
+    for monitor in &monitors {
+        monitor.connect_changed(clone! {
+            #[strong] update,
+            #[strong] popup,
+            #[strong] hover,
+            move |_, _, _, _| {
+                let percent = update();
+                libs::autohide::request_attention();
+                // no need for the osd while the pointer is on the module itself
+                if CONFIG.backlight.osd && !hover.contains_pointer() {
+                    popup.update(percent);
+                    popup.present(true);
+                }
+            }
+        });
+    }
+    // the monitors stop once they're dropped, so they live as long as the widget
+    widget.connect_destroy(move |_| {
+        let _ = &monitors;
+    });
+    Some(widget)
+}
@@ -1,5 +1,25 @@
 use crate::*;
+use libs::icons::Icon;
+use libs::tooltip;
+use std::cell::RefCell;
 use std::path::Path;
+use std::rc::Rc;
+use widgets::power_profiles;
+
+/// `1h 05m`
+fn duration(hours: f64) -> String {
+    let minutes = (hours * 60.0).round() as u64;
+    format!("{}h {:02}m", minutes / 60, minutes % 60)
+}
+
+/// a number from the battery's sysfs directory
+fn read(name: &str) -> Option<f64> {
+    std::fs::read_to_string(format!("/sys/class/power_supply/BAT0/{name}"))
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
 
 pub struct Battery {
     pub widget: Box,
@@ -21,19 +41,90 @@ impl Battery {
     pub fn status() -> Option<String> {
         std::fs::read_to_string("/sys/class/power_supply/BAT0/status").ok()
     }
-    pub fn fmt() -> String {
+    /// hours until empty while discharging or until full while charging,
+    /// batteries report either energy and power or charge and current
+    pub fn time_left() -> Option<f64> {
+        let (now, full, rate) = match read("power_now") {
+            Some(power) => (read("energy_now")?, read("energy_full")?, power),
+            None => (
+                read("charge_now")?,
+                read("charge_full")?,
+                read("current_now")?,
+            ),
+        };
+        if rate <= 0.0 {
+            return None;
+        }
+        match Self::status()?.trim() {
+            "Charging" => Some((full - now).max(0.0) / rate),
+            "Discharging" => Some(now / rate),
+            _ => None,
+        }
+    }
+    pub fn tooltip(profile: Option<&str>) -> String {
+        let status = Self::status().unwrap_or_default();
+        let status = status.trim();
+        let mut lines = vec![format!(
+            "<b>{}%</b>  {}",
+            Self::percent().unwrap_or_default(),
+            tooltip::escape(status)
+        )];
+        if let Some(hours) = Self::time_left() {
+            let until = if status == "Charging" {
+                "until full"
+            } else {
+                "left"
+            };
+            lines.push(format!("{} {until}", duration(hours)));
+        }
+        if let Some(profile) = profile {
+            lines.push(tooltip::row("profile", profile));
+        }
+        lines.join("\n")
+    }
+    /// the glyph and the theme icon name, configured icons are used as either
+    fn icons(percent: usize, status: &str) -> (&'static str, String) {
+        if let Some(configured) = CONFIG.battery.icon(status, percent as f64) {
+            return (configured, configured.to_string());
+        }
+        let glyphs = ["󰂎", "󰁺", "󰁻", "󰁼", "󰁽", "󰁾", "󰁿", "󰂀", "󰂁", "󰁹", "󰂃"];
+        let level = percent.min(100) / 10 * 10;
+        let name = match status {
+            "charging" | "full" if level == 100 => "battery-level-100-charged-symbolic".to_string(),
+            "charging" => format!("battery-level-{level}-charging-symbolic"),
+            _ => format!("battery-level-{level}-symbolic"),
+        };
+        (glyphs[level / 10], name)
+    }
+    /// the label text, also switches `icon` to the theme icon when there is one
+    pub fn fmt(icon: &Icon) -> String {
         if let Some(percent) = Self::percent() {
-            let status = Self::status().unwrap_or("󰂃 ".to_string());
-            let icons = ["󰂎", "󰁺", "󰁻", "󰁼", "󰁽", "󰁾", "󰁿", "󰂀", "󰂁", "󰁹", "󰂃"];
-            format!(
-                "{}{} {}",
-                icons[percent / 10],
-                if status.trim() == "Charging" {
-                    "󱐋"
-                } else {
-                    " "
-                },
-                percent,
+            // the state for icon sets, `charging`, `discharging`, `full` or `not charging`
+            let status = Self::status().unwrap_or_default().trim().to_lowercase();
+            let (glyph, name) = Self::icons(percent, &status);
+            // the theme icon stands in for both the glyph and the bolt
+            let theme = icon.uses_theme(&name);
+            if theme {
+                icon.set(glyph, &name);
+            }
+            icon.widget.set_visible(theme);
+            let charging = match (theme, status.as_str()) {
+                (true, _) => "",
+                (false, "charging") => "󱐋",
+                (false, _) => " ",
+            };
+            CONFIG.battery.render(
+                "{icon}{charging} {percent}",
+                &[
+                    ("icon", if theme { "" } else { glyph }.into()),
+                    ("charging", charging.into()),
+                    ("percent", (percent as f64).into()),
+                    ("status", status.as_str().into()),
+                    (
+                        "time",
+                        Self::time_left().map(duration).unwrap_or_default().into(),
+                    ),
+                ],
             )
         } else {
             String::new()
@@ -42,11 +133,23 @@ impl Battery {
     pub fn new() -> Option<Self> {
         if Self::has() {
             let widget = Box::default();
-            let batt = Label::new(Some(&Self::fmt()));
+            let icon = Icon::new("battery");
+            let batt = Label::new(Some(&Self::fmt(&icon)));
             batt.add_css_class("battery");
+            widget.append(&icon.widget);
             widget.append(&batt);
+            let profile = Rc::new(RefCell::new(None));
+            power_profiles::watch(clone! {
+                #[strong] profile,
+                move |active: &str| {
+                    profile.replace(Some(active.to_string()));
+                }
+            });
+            tooltip::lazy(&batt, move || {
+                Some(Self::tooltip(profile.borrow().as_deref()))
+            });
             timeout_add_local(Duration::from_secs(1), move || {
-                batt.set_label(&Self::fmt());
+                batt.set_label(&Self::fmt(&icon));
                 ControlFlow::Continue
             });
             Some(Self { widget })
@@ -0,0 +1,303 @@
+use crate::*;
+use anyhow::Result;
+use gio::DBusConnection;
+use glib::VariantDict;
+use gtk::{Popover, Switch};
+use libs::icons::Icon;
+use libs::{dbus, tooltip};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+const BLUEZ: &str = "org.bluez";
+const ADAPTER: &str = "org.bluez.Adapter1";
+const DEVICE: &str = "org.bluez.Device1";
+const BATTERY: &str = "org.bluez.Battery1";
+
+#[derive(Clone)]
+struct Device {
+    path: String,
+    name: String,
+    connected: bool,
+    battery: Option<u8>,
+}
+
+impl Device {
+    fn label(&self) -> String {
+        match self.battery {
+            Some(battery) => format!("{} {battery}%", self.name),
+            None => self.name.clone(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Status {
+    /// the first adapter, none when there's no bluetooth at all
+    adapter: Option<String>,
+    powered: bool,
+    /// paired devices, connected ones first
+    devices: Vec<Device>,
+}
+
+async fn status(conn: &DBusConnection) -> Result<Status> {
+    let reply = dbus::call(
+        conn,
+        BLUEZ,
+        "/",
+        "org.freedesktop.DBus.ObjectManager",
+        "GetManagedObjects",
+        None,
+    )
+    .await?;
+    let mut status = Status::default();
+    // a{oa{sa{sv}}}, object path to interface to its properties
+    for object in reply.child_value(0).iter() {
+        let Some(path) = object.child_value(0).str().map(str::to_string) else {
+            continue;
+        };
+        let interfaces: HashMap<String, VariantDict> = object
+            .child_value(1)
+            .iter()
+            .filter_map(|i| {
+                let iface = i.child_value(0).str()?.to_string();
+                Some((iface, VariantDict::new(Some(&i.child_value(1)))))
+            })
+            .collect();
+        if let Some(adapter) = interfaces.get(ADAPTER) {
+            if status.adapter.is_none() {
+                status.powered = adapter.lookup::<bool>("Powered")?.unwrap_or(false);
+                status.adapter = Some(path.clone());
+            }
+        }
+        if let Some(device) = interfaces.get(DEVICE) {
+            if !device.lookup::<bool>("Paired")?.unwrap_or(false) {
+                continue;
+            }
+            let name = match device.lookup::<String>("Alias")? {
+                Some(name) => name,
+                None => device.lookup::<String>("Address")?.unwrap_or_default(),
+            };
+            let battery = match interfaces.get(BATTERY) {
+                Some(battery) => battery.lookup::<u8>("Percentage")?,
+                None => None,
+            };
+            status.devices.push(Device {
+                path,
+                name,
+                connected: device.lookup::<bool>("Connected")?.unwrap_or(false),
+                battery,
+            });
+        }
+    }
+    status
+        .devices
+        .sort_by(|a, b| b.connected.cmp(&a.connected).then(a.name.cmp(&b.name)));
+    Ok(status)
+}
+
+#[derive(Clone)]
+struct Bluetooth {
+    widget: Box,
+    icon: Icon,
+    label: Label,
+    menu: Popover,
+    power: Switch,
+    devices: Box,
+    adapter: Rc<RefCell<Option<String>>>,
+    // set while the switch is moved to match bluez so it doesn't write the state back
+    syncing: Rc<Cell<bool>>,
+}
+
+impl Bluetooth {
+    fn new() -> Self {
+        let widget = Box::new(Horizontal, 5);
+        widget.add_css_class("container");
+        widget.add_css_class("bluetooth");
+        widget.set_visible(false);
+        let icon = Icon::new("bluetooth");
+        let label = Label::new(None);
+        widget.append(&icon.widget);
+        widget.append(&label);
+        let menu = Popover::builder().has_arrow(false).build();
+        let content = Box::new(Vertical, 5);
+        let header = Box::new(Horizontal, 10);
+        let power = Switch::new();
+        let devices = Box::new(Vertical, 0);
+        content.add_css_class("bluetooth-menu");
+        header.append(&Label::new(Some("Bluetooth")));
+        header.append(&power);
+        content.append(&header);
+        content.append(&devices);
+        menu.set_child(Some(&content));
+        menu.set_parent(&widget);
+        Self {
+            widget,
+            icon,
+            label,
+            menu,
+            power,
+            devices,
+            adapter: Rc::new(RefCell::new(None)),
+            syncing: Rc::new(Cell::new(false)),
+        }
+    }
+    fn show(&self, status: &Status, conn: &DBusConnection) {
+        self.widget.set_visible(status.adapter.is_some());
+        self.adapter.replace(status.adapter.clone());
+        let connected: Vec<&Device> = status.devices.iter().filter(|d| d.connected).collect();
+        let (glyph, name) = match (status.powered, connected.is_empty()) {
+            (false, _) => ("󰂲", "bluetooth-disabled-symbolic"),
+            (true, true) => ("󰂯", "bluetooth-active-symbolic"),
+            (true, false) => ("󰂱", "bluetooth-active-symbolic"),
+        };
+        self.icon.set(glyph, name);
+        let devices = connected
+            .iter()
+            .map(|d| d.label())
+            .collect::<Vec<String>>()
+            .join(", ");
+        let label = CONFIG.bluetooth.render(
+            "{devices}",
+            &[
+                ("devices", devices.into()),
+                ("count", (connected.len() as f64).into()),
+            ],
+        );
+        self.label.set_text(&label);
+        self.label.set_visible(!label.trim().is_empty());
+        let lines: Vec<String> = match (status.powered, connected.is_empty()) {
+            (false, _) => vec!["bluetooth is off".to_string()],
+            (true, true) => vec!["no device connected".to_string()],
+            (true, false) => connected
+                .iter()
+                .map(|d| match d.battery {
+                    Some(battery) => tooltip::row(&d.name, &format!("{battery}%")),
+                    None => tooltip::escape(&d.name),
+                })
+                .collect(),
+        };
+        tooltip::set(&self.widget, &lines.join("\n"));
+        self.syncing.set(true);
+        self.power.set_active(status.powered);
+        self.syncing.set(false);
+        while let Some(child) = self.devices.first_child() {
+            self.devices.remove(&child);
+        }
+        for device in &status.devices {
+            let button = Button::with_label(&device.label());
+            button.set_sensitive(status.powered);
+            if device.connected {
+                button.add_css_class("active");
+            }
+            button.connect_clicked(clone! {
+                #[strong] conn,
+                #[strong] device,
+                move |_| {
+                    spawn_future_local(clone! {
+                        #[strong] conn,
+                        #[strong] device,
+                        async move {
+                            let method = if device.connected { "Disconnect" } else { "Connect" };
+                            let result =
+                                dbus::call(&conn, BLUEZ, &device.path, DEVICE, method, None).await;
+                            if let Err(e) = result {
+                                eprintln!("bluetooth: {}: {e}", device.name);
+                            }
+                        }
+                    });
+                }
+            });
+            self.devices.append(&button);
+        }
+    }
+}
+
+async fn listen(bluetooth: Bluetooth, conn: DBusConnection) {
+    let refresh = clone! {
+        #[strong] bluetooth,
+        #[strong] conn,
+        move || {
+            spawn_future_local(clone! {
+                #[strong] bluetooth,
+                #[strong] conn,
+                async move {
+                    let status = status(&conn).await.unwrap_or_default();
+                    bluetooth.show(&status, &conn);
+                }
+            });
+        }
+    };
+    refresh();
+    // connecting a device fires a burst of property changes, so updates are batched
+    let pending = Rc::new(Cell::new(false));
+    let schedule = Rc::new(move || {
+        if !pending.get() {
+            pending.set(true);
+            spawn_future_local(clone! {
+                #[strong] pending,
+                #[strong] refresh,
+                async move {
+                    sleep(Duration::from_millis(200)).await;
+                    pending.set(false);
+                    refresh();
+                }
+            });
+        }
+    });
+    for member in ["InterfacesAdded", "InterfacesRemoved"] {
+        dbus::on_signal(
+            &conn,
+            BLUEZ,
+            "org.freedesktop.DBus.ObjectManager",
+            member,
+            None,
+            clone! {
+                #[strong] schedule,
+                move |_, _| schedule()
+            },
+        );
+    }
+    dbus::on_properties_changed(&conn, BLUEZ, None, move |_, _, _| schedule());
+    bluetooth.power.connect_state_set(clone! {
+        #[strong] bluetooth,
+        #[strong] conn,
+        move |_, powered| {
+            let adapter = bluetooth.adapter.borrow().clone();
+            if let (Some(adapter), false) = (adapter, bluetooth.syncing.get()) {
+                spawn_future_local(clone! {
+                    #[strong] conn,
+                    async move {
+                        let powered = powered.to_variant();
+                        let result =
+                            dbus::set_property(&conn, BLUEZ, &adapter, ADAPTER, "Powered", powered)
+                                .await;
+                        if let Err(e) = result {
+                            eprintln!("bluetooth: {e}");
+                        }
+                    }
+                });
+            }
+            Propagation::Proceed
+        }
+    });
+    let click = GestureClick::new();
+    click.connect_pressed(clone! {
+        #[strong] bluetooth,
+        move |_, _, _, _| bluetooth.menu.popup()
+    });
+    bluetooth.widget.add_controller(click);
+}
+
+pub fn new() -> Box {
+    let bluetooth = Bluetooth::new();
+    spawn_future_local(clone! {
+        #[strong] bluetooth,
+        async move {
+            match dbus::system().await {
+                Ok(conn) => listen(bluetooth, conn).await,
+                Err(e) => eprintln!("bluetooth: {e}"),
+            }
+        }
+    });
+    bluetooth.widget
+}
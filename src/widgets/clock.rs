@@ -1,21 +1,46 @@
 use crate::*;
+use libs::icons::Icon;
+use libs::tooltip;
+use std::fmt::Write;
+
+/// the template with `{time}` as the blinking `%H : %M`, then run through strftime
+fn label(colon: bool) -> String {
+    let now = Local::now();
+    let time = now.format(if colon { "%H : %M" } else { "%H   %M" });
+    let template = CONFIG
+        .clock
+        .render("{time}", &[("time", time.to_string().into())]);
+    let mut out = String::new();
+    match write!(out, "{}", now.format(&template)) {
+        Ok(()) => out,
+        Err(_) => template,
+    }
+}
 
 pub fn new() -> Box {
     let widget = Box::new(Horizontal, 5);
-    let icon = Label::new(Some("󰥔 "));
-    let time = Label::new(Some(&Local::now().format("%H : %M").to_string()));
-    widget.append(&icon);
+    let icon = Icon::new("clock");
+    icon.set("󰥔 ", "preferences-system-time-symbolic");
+    let time = Label::new(Some(&label(true)));
+    widget.append(&icon.widget);
     widget.append(&time);
+    tooltip::lazy(&widget, || {
+        let now = Local::now();
+        // a bad format from the config is an error here rather than a panic
+        let mut date = String::new();
+        write!(date, "{}", now.format(&CONFIG.tooltips.date_format)).ok()?;
+        Some(format!(
+            "<b>{}</b>\n{}",
+            tooltip::escape(&date),
+            now.format("%H:%M:%S, week %V")
+        ))
+    });
     timeout_add_local(Duration::from_secs(1), move || {
         let now = Local::now();
         let sec = format!("{}", now.format("%S"))
             .parse::<i32>()
             .expect("Datetime is broken some how");
-        if sec % 2 == 0 {
-            time.set_label(&format!("{}", now.format("%H : %M")));
-        } else {
-            time.set_label(&format!("{}", now.format("%H   %M")))
-        }
+        time.set_label(&label(sec % 2 == 0));
         ControlFlow::Continue
     });
     widget
@@ -0,0 +1,244 @@
+use crate::*;
+use libs::config::Custom;
+use libs::{control, process, signals, tooltip};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct Output {
+    text: String,
+    tooltip: Option<String>,
+    class: Classes,
+    percentage: Option<f64>,
+}
+
+/// `class` can be a single class or a list of them
+#[derive(Deserialize, Default)]
+#[serde(untagged)]
+enum Classes {
+    #[default]
+    None,
+    One(String),
+    Many(Vec<String>),
+}
+
+impl Classes {
+    fn list(self) -> Vec<String> {
+        match self {
+            Self::None => Vec::new(),
+            Self::One(class) => vec![class],
+            Self::Many(classes) => classes,
+        }
+    }
+}
+
+/// json when it looks like an object, otherwise the lines are text, tooltip and class
+fn parse(output: &str) -> Output {
+    let output = output.trim();
+    if output.starts_with('{') {
+        if let Ok(parsed) = from_str::<Output>(output) {
+            return parsed;
+        }
+    }
+    let mut lines = output.lines();
+    Output {
+        text: lines.next().unwrap_or_default().to_string(),
+        tooltip: lines.next().map(str::to_string),
+        class: lines
+            .next()
+            .map_or(Classes::None, |c| Classes::One(c.to_string())),
+        percentage: None,
+    }
+}
+
+/// scripts don't always escape what they print, so broken markup is shown as it is
+fn set_markup(label: &Label, text: &str) {
+    if gtk::pango::parse_markup(text, '\0').is_ok() {
+        label.set_markup(text);
+    } else {
+        label.set_text(text);
+    }
+}
+
+fn run_detached(command: &Option<String>) {
+    if let Some(command) = command {
+        if let Err(e) = process::sh(command) {
+            eprintln!("custom: {command}: {e}");
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Module {
+    config: &'static Custom,
+    /// the configured name, or the module's place in the config when it has none
+    name: String,
+    widget: Box,
+    label: Label,
+    /// classes the command set last time, so they can be taken off again
+    classes: Rc<RefCell<Vec<String>>>,
+    /// set while the command runs so a slow one isn't started again on top of itself
+    running: Rc<Cell<bool>>,
+}
+
+impl Module {
+    fn show(&self, output: Output) {
+        let classes = output.class.list();
+        let state = classes.first().map_or("default", String::as_str);
+        let icon = output
+            .percentage
+            .and_then(|percentage| self.config.label.icon(state, percentage));
+        let default = match icon {
+            Some(_) => "{icon} {text}",
+            None => "{text}",
+        };
+        let text = self.config.label.render(
+            default,
+            &[
+                ("text", output.text.into()),
+                ("icon", icon.unwrap_or_default().into()),
+                ("percentage", output.percentage.unwrap_or_default().into()),
+            ],
+        );
+        self.widget.set_visible(!text.trim().is_empty());
+        set_markup(&self.label, &text);
+        match output.tooltip {
+            Some(text) if gtk::pango::parse_markup(&text, '\0').is_ok() => {
+                tooltip::set(&self.widget, &text)
+            }
+            text => tooltip::text(&self.widget, text.as_deref().unwrap_or_default()),
+        }
+        for class in self.classes.borrow().iter() {
+            self.widget.remove_css_class(class);
+        }
+        for class in &classes {
+            self.widget.add_css_class(class);
+        }
+        self.classes.replace(classes);
+    }
+    /// runs the command once and shows what it printed, nothing happens while it still runs
+    fn refresh(&self) {
+        if self.running.replace(true) {
+            return;
+        }
+        spawn_future_local(clone! {
+            #[strong(rename_to = this)] self,
+            async move {
+                match process::output(&["sh", "-c", this.config.exec.as_str()]).await {
+                    Ok(output) => this.show(parse(&output)),
+                    Err(e) => eprintln!("custom/{}: {e}", this.name),
+                }
+                this.running.set(false);
+            }
+        });
+    }
+    /// keeps the command running, every line it prints is a new state
+    async fn stream(&self) {
+        loop {
+            let argv = ["sh", "-c", self.config.exec.as_str()];
+            if let Err(e) = process::lines(&argv, |line| self.show(parse(line))).await {
+                eprintln!("custom/{}: {e}", self.name);
+            }
+            let Some(interval) = self.config.interval else {
+                break;
+            };
+            sleep(Duration::from_millis(interval)).await;
+        }
+    }
+}
+
+/// `index` names the module when the config doesn't
+pub fn new(index: usize, config: &'static Custom) -> Box {
+    let widget = Box::new(Horizontal, 5);
+    widget.add_css_class("container");
+    widget.add_css_class("custom");
+    if !config.name.is_empty() {
+        widget.add_css_class(&config.name);
+    }
+    widget.set_visible(false);
+    let label = Label::new(None);
+    widget.append(&label);
+    let name = match config.name.as_str() {
+        "" => index.to_string(),
+        name => name.to_string(),
+    };
+    let module = Module {
+        config,
+        name,
+        widget,
+        label,
+        classes: Rc::new(RefCell::new(Vec::new())),
+        running: Rc::new(Cell::new(false)),
+    };
+
+    let click = GestureClick::builder().button(0).build();
+    click.connect_pressed(clone! {
+        #[strong] module,
+        move |gesture, _, _, _| {
+            let command = match gesture.current_button() {
+                1 => &config.on_click,
+                2 => &config.on_middle_click,
+                3 => &config.on_right_click,
+                _ => &None,
+            };
+            run_detached(command);
+            // what the click did most likely shows up on the next run
+            if command.is_some() && !config.stream {
+                module.refresh();
+            }
+        }
+    });
+    module.widget.add_controller(click);
+    let scroll = EventControllerScroll::new(EventControllerScrollFlags::VERTICAL);
+    scroll.connect_scroll(move |_, _, dy| {
+        if dy < -0.5 {
+            run_detached(&config.on_scroll_up);
+        } else if dy > 0.5 {
+            run_detached(&config.on_scroll_down);
+        }
+        Propagation::Stop
+    });
+    module.widget.add_controller(scroll);
+
+    if config.stream {
+        spawn_future_local(clone! {
+            #[strong] module,
+            async move { module.stream().await }
+        });
+    } else {
+        module.refresh();
+        if let Some(interval) = config.interval.filter(|i| *i > 0) {
+            spawn_future_local(clone! {
+                #[strong] module,
+                async move {
+                    loop {
+                        sleep(Duration::from_millis(interval)).await;
+                        module.refresh();
+                    }
+                }
+            });
+        }
+        if let Some(signal) = config.signal {
+            signals::on_rt_signal(
+                signal,
+                clone! {
+                    #[strong] module,
+                    move || module.refresh()
+                },
+            );
+        }
+        // `gtk-bar msg custom/<name>` runs it right away
+        control::register(
+            &format!("custom/{}", module.name),
+            clone! {
+                #[strong] module,
+                move |_: &[&str]| {
+                    module.refresh();
+                    "ok".to_string()
+                }
+            },
+        );
+    }
+    module.widget
+}
@@ -0,0 +1,173 @@
+use crate::*;
+use libs::icons::Icon;
+use libs::{process, tooltip};
+use std::ffi::CString;
+use widgets::sysmon::{interval, threshold};
+
+struct Usage {
+    total: u64,
+    used: u64,
+    /// what an unprivileged user can still write
+    available: u64,
+}
+
+impl Usage {
+    /// the same percentage `df` shows, reserved blocks don't count as free
+    fn percent(&self) -> f64 {
+        let usable = self.used + self.available;
+        if usable == 0 {
+            0.0
+        } else {
+            self.used as f64 / usable as f64 * 100.0
+        }
+    }
+}
+
+fn statvfs(path: &str) -> Option<Usage> {
+    let path = CString::new(path).ok()?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    let block = stat.f_frsize as u64;
+    Some(Usage {
+        total: stat.f_blocks as u64 * block,
+        used: (stat.f_blocks as u64).saturating_sub(stat.f_bfree as u64) * block,
+        available: stat.f_bavail as u64 * block,
+    })
+}
+
+fn size(bytes: u64) -> String {
+    let mut value = bytes as f64;
+    for unit in ["B", "K", "M", "G"] {
+        if value < 1024.0 {
+            return format!("{value:.1}{unit}");
+        }
+        value /= 1024.0;
+    }
+    format!("{value:.1}T")
+}
+
+/// mount points of filesystems backed by a device, `/proc/filesystems` marks the others `nodev`
+fn mounts() -> Vec<String> {
+    let filesystems = std::fs::read_to_string("/proc/filesystems").unwrap_or_default();
+    let virtual_fs: Vec<&str> = filesystems
+        .lines()
+        .filter_map(|l| l.strip_prefix("nodev"))
+        .map(str::trim)
+        .collect();
+    let mounts = std::fs::read_to_string("/proc/self/mounts").unwrap_or_default();
+    let mut result: Vec<String> = Vec::new();
+    for line in mounts.lines() {
+        // /dev/nvme0n1p2 / btrfs rw,relatime 0 0
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let [_, mount, fs, ..] = fields[..] else {
+            continue;
+        };
+        // spaces and the like are escaped as octal, e.g. \040
+        let mount = unescape(mount);
+        if !virtual_fs.contains(&fs) && !result.contains(&mount) {
+            result.push(mount);
+        }
+    }
+    result
+}
+
+fn unescape(s: &str) -> String {
+    let mut bytes = Vec::new();
+    let mut rest = s.as_bytes();
+    while let Some((&b, tail)) = rest.split_first() {
+        let code = tail
+            .get(..3)
+            .and_then(|o| std::str::from_utf8(o).ok())
+            .and_then(|o| u8::from_str_radix(o, 8).ok());
+        match (b, code) {
+            (b'\\', Some(code)) => {
+                bytes.push(code);
+                rest = &tail[3..];
+            }
+            _ => {
+                bytes.push(b);
+                rest = tail;
+            }
+        }
+    }
+    String::from_utf8_lossy(&bytes).to_string()
+}
+
+pub fn new(mount: &str) -> Box {
+    let config = &CONFIG.disk;
+    let widget = Box::new(Horizontal, 5);
+    widget.add_css_class("container");
+    widget.add_css_class("disk");
+    let icon = Icon::new("disk");
+    icon.set("󰋊", "drive-harddisk-symbolic");
+    let label = Label::new(None);
+    widget.append(&icon.widget);
+    widget.append(&label);
+    let click = GestureClick::new();
+    click.connect_pressed(clone! {
+        #[to_owned] mount,
+        move |_, _, _, _| {
+            if let Err(e) = process::spawn(&[config.file_manager.as_str(), mount.as_str()]) {
+                eprintln!("disk: {}: {e}", config.file_manager);
+            }
+        }
+    });
+    widget.add_controller(click);
+    spawn_future_local(clone! {
+        #[strong] widget,
+        #[to_owned] mount,
+        async move {
+            loop {
+                // statvfs blocks for as long as the filesystem takes to answer,
+                // a slow network mount must not hold up the bar
+                let (usage, all) = gio::spawn_blocking(clone! {
+                    #[strong] mount,
+                    move || {
+                        let all: Vec<(String, Usage)> = mounts()
+                            .into_iter()
+                            .filter_map(|m| statvfs(&m).map(|usage| (m, usage)))
+                            .collect();
+                        (statvfs(&mount), all)
+                    }
+                })
+                .await
+                .unwrap_or_default();
+                if let Some(usage) = usage {
+                    let percent = usage.percent();
+                    label.set_text(&config.label.render(
+                        "{percent}%",
+                        &[
+                            ("percent", percent.into()),
+                            ("used", size(usage.used).into()),
+                            ("total", size(usage.total).into()),
+                            ("free", size(usage.available).into()),
+                            ("mount", mount.as_str().into()),
+                        ],
+                    ));
+                    let state = threshold(&widget, percent, config.warning, config.critical);
+                    if let Some(i) = config.label.icon(state, percent) {
+                        icon.set(i, i);
+                    }
+                }
+                let tooltip = all
+                    .iter()
+                    .map(|(m, usage)| {
+                        let value = format!(
+                            "{} / {} ({} free)",
+                            size(usage.used),
+                            size(usage.total),
+                            size(usage.available)
+                        );
+                        tooltip::row(m, &value)
+                    })
+                    .collect::<Vec<String>>()
+                    .join("\n");
+                tooltip::set(&widget, &tooltip);
+                sleep(interval(config.interval)).await;
+            }
+        }
+    });
+    widget
+}
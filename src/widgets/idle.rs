@@ -0,0 +1,195 @@
+use crate::*;
+use anyhow::{bail, Result};
+use gio::{DBusCallFlags, UnixFDList};
+use libs::icons::Icon;
+use libs::{control, dbus, tooltip};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+/// whatever keeps the inhibition alive, letting go of it ends the inhibition
+enum Hold {
+    /// cookie from `org.freedesktop.ScreenSaver.Inhibit`, what hypridle and friends listen to
+    ScreenSaver(u32),
+    /// the fd logind hands out, it's released once the fd is closed
+    Logind(UnixFDList),
+}
+
+async fn inhibit() -> Result<Hold> {
+    let session = dbus::session().await?;
+    let screensaver = dbus::call(
+        &session,
+        "org.freedesktop.ScreenSaver",
+        "/org/freedesktop/ScreenSaver",
+        "org.freedesktop.ScreenSaver",
+        "Inhibit",
+        Some(&("gtk-bar", "idle inhibitor").to_variant()),
+    )
+    .await;
+    if let Some(cookie) = screensaver.ok().and_then(|r| r.child_value(0).get::<u32>()) {
+        return Ok(Hold::ScreenSaver(cookie));
+    }
+    let system = dbus::system().await?;
+    let (_, fds) = system
+        .call_with_unix_fd_list_future(
+            Some("org.freedesktop.login1"),
+            "/org/freedesktop/login1",
+            "org.freedesktop.login1.Manager",
+            "Inhibit",
+            Some(&("idle", "gtk-bar", "idle inhibitor", "block").to_variant()),
+            None,
+            DBusCallFlags::NONE,
+            -1,
+            None::<&UnixFDList>,
+        )
+        .await?;
+    Ok(Hold::Logind(fds))
+}
+
+async fn release(hold: Hold) -> Result<()> {
+    match hold {
+        Hold::ScreenSaver(cookie) => {
+            let session = dbus::session().await?;
+            dbus::call(
+                &session,
+                "org.freedesktop.ScreenSaver",
+                "/org/freedesktop/ScreenSaver",
+                "org.freedesktop.ScreenSaver",
+                "UnInhibit",
+                Some(&(cookie,).to_variant()),
+            )
+            .await?;
+        }
+        // closing the fd is all logind needs
+        Hold::Logind(fds) => drop(fds),
+    }
+    Ok(())
+}
+
+#[derive(Clone)]
+struct Inhibitor {
+    widget: Box,
+    icon: Icon,
+    hold: Rc<RefCell<Option<Hold>>>,
+    /// an inhibit or release is on its way, further toggles wait for it
+    pending: Rc<Cell<bool>>,
+}
+
+impl Inhibitor {
+    fn active(&self) -> bool {
+        self.hold.borrow().is_some()
+    }
+    fn show(&self) {
+        let active = self.active();
+        if active {
+            self.icon.set("󰅶", "caffeine-cup-full-symbolic");
+        } else {
+            self.icon.set("󰾪", "caffeine-cup-empty-symbolic");
+        }
+        if active {
+            self.widget.add_css_class("active");
+        } else {
+            self.widget.remove_css_class("active");
+        }
+        tooltip::set(
+            &self.widget,
+            &tooltip::row("idle", if active { "inhibited" } else { "allowed" }),
+        );
+    }
+    /// resolves once the inhibition is in the asked state, fails while another change is running
+    async fn set(&self, active: bool) -> Result<()> {
+        if self.pending.get() {
+            bail!("busy");
+        }
+        if active == self.active() {
+            return Ok(());
+        }
+        self.pending.set(true);
+        let result = if active {
+            match inhibit().await {
+                // nothing should be held here, but a hold that's dropped stays inhibited forever
+                Ok(hold) => match self.hold.replace(Some(hold)) {
+                    Some(old) => release(old).await,
+                    None => Ok(()),
+                },
+                Err(e) => Err(e),
+            }
+        } else {
+            match self.hold.take() {
+                Some(hold) => release(hold).await,
+                None => Ok(()),
+            }
+        };
+        self.pending.set(false);
+        self.show();
+        result
+    }
+}
+
+pub fn new() -> Box {
+    let widget = Box::new(Horizontal, 0);
+    widget.add_css_class("container");
+    widget.add_css_class("idle");
+    let icon = Icon::new("idle");
+    widget.append(&icon.widget);
+    let inhibitor = Inhibitor {
+        widget,
+        icon,
+        hold: Rc::new(RefCell::new(None)),
+        pending: Rc::new(Cell::new(false)),
+    };
+    inhibitor.show();
+    if CONFIG.idle.inhibit {
+        spawn_future_local(clone! {
+            #[strong] inhibitor,
+            async move {
+                if let Err(e) = inhibitor.set(true).await {
+                    eprintln!("idle: {e}");
+                }
+            }
+        });
+    }
+    let click = GestureClick::new();
+    click.connect_pressed(clone! {
+        #[strong] inhibitor,
+        move |_, _, _, _| {
+            // a click while the last one is still going is dropped
+            if inhibitor.pending.get() {
+                return;
+            }
+            spawn_future_local(clone! {
+                #[strong] inhibitor,
+                async move {
+                    if let Err(e) = inhibitor.set(!inhibitor.active()).await {
+                        eprintln!("idle: {e}");
+                    }
+                }
+            });
+        }
+    });
+    inhibitor.widget.add_controller(click);
+    // `gtk-bar msg idle on|off|toggle|status`
+    // answers once the change went through
+    control::register_async(
+        "idle",
+        clone! {
+            #[strong] inhibitor,
+            move |args: Vec<String>| {
+                let inhibitor = inhibitor.clone();
+                async move {
+                    let active = match args.first().map(String::as_str) {
+                        Some("on") => true,
+                        Some("off") => false,
+                        Some("toggle") => !inhibitor.active(),
+                        Some("status") | None => inhibitor.active(),
+                        Some(other) => return format!("error: unknown command {other}"),
+                    };
+                    match inhibitor.set(active).await {
+                        Ok(()) => String::from(if inhibitor.active() { "on" } else { "off" }),
+                        Err(e) => format!("error: {e}"),
+                    }
+                }
+            }
+        },
+    );
+    inhibitor.widget
+}
@@ -0,0 +1,98 @@
+use crate::*;
+use async_broadcast::Receiver;
+use hyprland::ctl;
+use libs::tooltip;
+
+#[derive(Deserialize)]
+struct Devices {
+    keyboards: Vec<Keyboard>,
+}
+
+#[derive(Deserialize)]
+struct Keyboard {
+    name: String,
+    active_keymap: String,
+    main: bool,
+}
+
+fn main_keyboard() -> Option<Keyboard> {
+    let devices = from_str::<Devices>(&ctl("j/devices")).ok()?;
+    let mut keyboards = devices.keyboards.into_iter();
+    let first = keyboards.next()?;
+    if first.main {
+        Some(first)
+    } else {
+        keyboards.find(|k| k.main).or(Some(first))
+    }
+}
+
+fn label(keymap: &str) -> String {
+    let short = CONFIG
+        .keyboard
+        .labels
+        .get(keymap)
+        .cloned()
+        .unwrap_or(keymap.to_string());
+    CONFIG.keyboard.label.render(
+        "{short}",
+        &[("short", short.into()), ("layout", keymap.into())],
+    )
+}
+
+pub fn new(mut event_listener: Receiver<String>) -> Box {
+    let widget = Box::new(Horizontal, 5);
+    widget.add_css_class("container");
+    widget.add_css_class("keyboard");
+    let layout = Label::new(None);
+    widget.append(&layout);
+    let keyboard = main_keyboard();
+    let name = keyboard.as_ref().map(|k| k.name.clone());
+    if let Some(keyboard) = &keyboard {
+        layout.set_text(&label(&keyboard.active_keymap));
+    }
+    let lclick = GestureClick::new();
+    let rclick = GestureClick::builder().button(3).build();
+    lclick.connect_pressed(clone! {
+        #[strong] name,
+        move |_, _, _, _| {
+            if let Some(name) = &name {
+                ctl(&format!("dispatch switchxkblayout {name} next"));
+            }
+        }
+    });
+    rclick.connect_pressed(clone! {
+        #[strong] name,
+        move |_, _, _, _| {
+            if let Some(name) = &name {
+                ctl(&format!("dispatch switchxkblayout {name} prev"));
+            }
+        }
+    });
+    widget.add_controller(lclick);
+    widget.add_controller(rclick);
+    // the label is usually shortened through the config, the tooltip has the full name
+    tooltip::lazy(&widget, || {
+        let keyboard = main_keyboard()?;
+        Some(format!(
+            "<b>{}</b>\n{}",
+            tooltip::escape(&keyboard.active_keymap),
+            tooltip::escape(&keyboard.name)
+        ))
+    });
+    spawn_future_local(async move {
+        loop {
+            if let Ok(event) = event_listener.recv().await {
+                // activelayout>>KEYBOARDNAME,LAYOUTNAME
+                if let Some(("activelayout", data)) = event.split_once(">>") {
+                    if let Some((keyboard, keymap)) = data.split_once(",") {
+                        // every keyboard reports its layout, only follow the main one
+                        if name.as_deref().map_or(true, |n| n == keyboard) {
+                            layout.set_text(&label(keymap));
+                        }
+                    }
+                }
+            }
+        }
+    });
+    widget
+}
@@ -1,8 +1,23 @@
+pub mod backlight;
 pub mod battery;
+pub mod bluetooth;
 pub mod clock;
+pub mod custom;
+pub mod disk;
+pub mod idle;
+pub mod keyboard;
 pub mod music;
+pub mod network;
+pub mod notifications;
 pub mod popup;
+pub mod power;
+pub mod power_profiles;
+pub mod privacy;
 pub mod root;
+pub mod submap;
+pub mod sysmon;
 pub mod systray;
+pub mod temperature;
+pub mod throughput;
 pub mod volume;
 pub mod workspaces;
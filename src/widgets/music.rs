@@ -1,5 +1,7 @@
 use crate::*;
-use libs::shared_widget::CrossfadeIn;
+use libs::animation::{self, Sequence, Transition};
+use libs::icons::Icon;
+use libs::tooltip;
 use mpd_client::{
     client::{ConnectionEvent, Subsystem},
     commands::{CurrentSong, Next, Previous, SetPause, Stats, Status},
@@ -16,23 +18,22 @@ struct PPButton {
     widget: Overlay,
     icon_revealer: Revealer,
     state_revealer: Revealer,
-    state: Label,
+    state: Icon,
 }
 
 impl PPButton {
     fn new() -> Self {
         let widget = Overlay::new();
         widget.add_css_class("icon-container");
-        let icon_revealer = Revealer::builder()
-            .transition_type(Crossfade)
-            .child(&Label::new(Some("󰎆")))
-            .reveal_child(true)
-            .build();
-        let state = Label::new(None);
-        let state_revealer = Revealer::builder()
-            .transition_type(Crossfade)
-            .child(&state)
-            .build();
+        let icon = Icon::new("music");
+        icon.set("󰎆", "audio-x-generic-symbolic");
+        let controls = CONFIG.animation.durations.music_controls;
+        let icon_revealer = animation::revealer(Transition::Fade, controls);
+        icon_revealer.set_child(Some(&icon.widget));
+        icon_revealer.set_reveal_child(true);
+        let state = Icon::new("music");
+        let state_revealer = animation::revealer(Transition::Fade, controls);
+        state_revealer.set_child(Some(&state.widget));
         icon_revealer.add_css_class("icon");
         state_revealer.add_css_class("icon");
         let hover = EventControllerMotion::new();
@@ -64,9 +65,9 @@ impl PPButton {
     }
     fn set_state(&self, state: PlayState) {
         match state {
-            PlayState::Stopped => self.state.set_text("󰐌"),
-            PlayState::Playing => self.state.set_text("󰏥"),
-            PlayState::Paused => self.state.set_text("󰐌"),
+            PlayState::Stopped => self.state.set("󰐌", "media-playback-start-symbolic"),
+            PlayState::Playing => self.state.set("󰏥", "media-playback-pause-symbolic"),
+            PlayState::Paused => self.state.set("󰐌", "media-playback-start-symbolic"),
         }
     }
 }
@@ -74,14 +75,15 @@ impl PPButton {
 async fn left_ctl(mpd: &Client) -> (Box, PPButton) {
     let widget = Box::new(Horizontal, 5);
     let play_pause_button = PPButton::new();
-    let prev_button = Revealer::builder()
-        .transition_type(Crossfade)
-        .child(&Label::new(Some("󰒮")))
-        .build();
-    let next_button = Revealer::builder()
-        .transition_type(Crossfade)
-        .child(&Label::new(Some("󰒭")))
-        .build();
+    let prev_icon = Icon::new("music");
+    let next_icon = Icon::new("music");
+    prev_icon.set("󰒮", "media-skip-backward-symbolic");
+    next_icon.set("󰒭", "media-skip-forward-symbolic");
+    let controls = CONFIG.animation.durations.music_controls;
+    let prev_button = animation::revealer(Transition::Fade, controls);
+    let next_button = animation::revealer(Transition::Fade, controls);
+    prev_button.set_child(Some(&prev_icon.widget));
+    next_button.set_child(Some(&next_icon.widget));
     prev_button.add_css_class("hidden");
     next_button.add_css_class("hidden");
     let hover = EventControllerMotion::new();
@@ -172,20 +174,25 @@ struct Info {
     widget: Box,
     title: Label,
     artists: Label,
-    title_crossfade_in: CrossfadeIn,
-    artists_crossfade_in: CrossfadeIn,
-    separator_crossade_in: CrossfadeIn,
+    title_crossfade_in: Sequence,
+    artists_crossfade_in: Sequence,
+    separator_crossade_in: Sequence,
 }
 
 impl Info {
     fn new() -> Self {
         let widget = Box::new(Horizontal, 10);
-        let duration = Duration::from_millis(1500);
+        // slides in to make room, then fades in
+        let durations = &CONFIG.animation.durations;
+        let stages = [
+            (Transition::Slide, durations.music_slide),
+            (Transition::Fade, durations.music_fade),
+        ];
         let title = &Label::new(None);
         let artists = &Label::new(None);
-        let title_crossfade_in = CrossfadeIn::new(title, duration);
-        let artists_crossfade_in = CrossfadeIn::new(artists, duration);
-        let separator_crossade_in = CrossfadeIn::new(&Label::new(Some("-")), duration);
+        let title_crossfade_in = Sequence::new(title, &stages);
+        let artists_crossfade_in = Sequence::new(artists, &stages);
+        let separator_crossade_in = Sequence::new(&Label::new(Some("-")), &stages);
         separator_crossade_in.widget.add_css_class("hidden");
         artists_crossfade_in.widget.add_css_class("hidden");
         let hover = EventControllerMotion::new();
@@ -240,7 +247,16 @@ impl Info {
             separator_crossade_in,
         }
     }
-    fn update(&self, title: Option<&str>, artists: Option<String>) {
+    fn update(&self, title: Option<&str>, artists: Option<String>, album: Option<&str>) {
+        let rows = [
+            title.map(|t| format!("<b>{}</b>", tooltip::escape(t))),
+            artists.as_deref().map(tooltip::escape),
+            album.map(|a| format!("<i>{}</i>", tooltip::escape(a))),
+        ];
+        tooltip::set(
+            &self.widget,
+            &rows.into_iter().flatten().collect::<Vec<_>>().join("\n"),
+        );
         if let Some(title) = title {
             self.title.set_text(title);
             self.title_crossfade_in.reveal(true);
@@ -277,10 +293,14 @@ pub fn new() -> Box {
                             ppbutton.set_state(status.state);
                             if !(status.state == PlayState::Stopped) {
                                 if let Ok(Some(current)) = mpd.command(CurrentSong).await {
-                                    info.update(current.song.title(), current.song.artists().get(0).cloned());
+                                    info.update(
+                                        current.song.title(),
+                                        current.song.artists().get(0).cloned(),
+                                        current.song.album(),
+                                    );
                                 }
                             } else {
-                                info.update(None, None);
+                                info.update(None, None, None);
                             }
                         }
                     }
@@ -0,0 +1,590 @@
+use crate::*;
+use anyhow::Result;
+use gio::DBusConnection;
+use glib::{Variant, VariantDict};
+use gtk::{Popover, Switch};
+use libs::dbus::{self, object_path};
+use libs::icons::Icon;
+use libs::tooltip;
+use std::cell::Cell;
+use std::rc::Rc;
+
+const NM: &str = "org.freedesktop.NetworkManager";
+const NM_PATH: &str = "/org/freedesktop/NetworkManager";
+const NM_SETTINGS_PATH: &str = "/org/freedesktop/NetworkManager/Settings";
+const NM_DEVICE_TYPE_WIFI: u32 = 2;
+
+#[derive(Default, Clone, PartialEq)]
+enum State {
+    #[default]
+    Disconnected,
+    Wired(String),
+    Wifi {
+        ssid: String,
+        strength: u8,
+    },
+}
+
+#[derive(Default)]
+struct Status {
+    state: State,
+    vpn: Option<String>,
+    wifi_enabled: bool,
+    /// the active wifi connection, used to disconnect
+    wifi_connection: Option<String>,
+}
+
+#[derive(Clone)]
+struct AccessPoint {
+    path: String,
+    ssid: String,
+    strength: u8,
+    secure: bool,
+}
+
+fn wifi_icon(strength: u8) -> &'static str {
+    ["󰤯", "󰤟", "󰤢", "󰤥", "󰤨"][(strength.min(100) as usize * 4 + 50) / 100]
+}
+
+/// the theme icon matching `wifi_icon`
+fn wifi_icon_name(strength: u8) -> &'static str {
+    [
+        "network-wireless-signal-none-symbolic",
+        "network-wireless-signal-weak-symbolic",
+        "network-wireless-signal-ok-symbolic",
+        "network-wireless-signal-good-symbolic",
+        "network-wireless-signal-excellent-symbolic",
+    ][(strength.min(100) as usize * 4 + 50) / 100]
+}
+
+async fn access_point(conn: &DBusConnection, path: &str) -> Result<AccessPoint> {
+    let props =
+        dbus::properties(conn, NM, path, "org.freedesktop.NetworkManager.AccessPoint").await?;
+    let ssid = props.lookup::<Vec<u8>>("Ssid")?.unwrap_or_default();
+    Ok(AccessPoint {
+        path: path.to_string(),
+        ssid: String::from_utf8_lossy(&ssid).to_string(),
+        strength: props.lookup::<u8>("Strength")?.unwrap_or(0),
+        secure: props.lookup::<u32>("WpaFlags")?.unwrap_or(0) != 0
+            || props.lookup::<u32>("RsnFlags")?.unwrap_or(0) != 0,
+    })
+}
+
+async fn status(conn: &DBusConnection) -> Result<Status> {
+    let nm = dbus::properties(conn, NM, NM_PATH, NM).await?;
+    let mut status = Status {
+        wifi_enabled: nm.lookup::<bool>("WirelessEnabled")?.unwrap_or(false),
+        ..Default::default()
+    };
+    let primary = nm
+        .lookup_value("PrimaryConnection", None)
+        .map(|v| dbus::strings(&v))
+        .unwrap_or_default();
+    let active = nm
+        .lookup_value("ActiveConnections", None)
+        .map(|v| dbus::strings(&v))
+        .unwrap_or_default();
+    for path in active {
+        let props = dbus::properties(
+            conn,
+            NM,
+            &path,
+            "org.freedesktop.NetworkManager.Connection.Active",
+        )
+        .await?;
+        let kind = props.lookup::<String>("Type")?.unwrap_or_default();
+        let id = props.lookup::<String>("Id")?.unwrap_or_default();
+        if props.lookup::<bool>("Vpn")?.unwrap_or(false) || kind == "wireguard" {
+            status.vpn = Some(id);
+            continue;
+        }
+        // the primary connection wins, anything else only fills in when there's nothing yet
+        if status.state != State::Disconnected && !primary.contains(&path) {
+            continue;
+        }
+        if kind == "802-11-wireless" {
+            let ap = props
+                .lookup_value("SpecificObject", None)
+                .and_then(|v| v.str().map(str::to_string))
+                .unwrap_or_default();
+            let (ssid, strength) = match access_point(conn, &ap).await {
+                Ok(ap) => (ap.ssid, ap.strength),
+                Err(_) => (id, 0),
+            };
+            status.state = State::Wifi { ssid, strength };
+            status.wifi_connection = Some(path);
+        } else {
+            status.state = State::Wired(id);
+        }
+    }
+    Ok(status)
+}
+
+async fn wifi_device(conn: &DBusConnection) -> Option<String> {
+    let devices = dbus::call(conn, NM, NM_PATH, NM, "GetDevices", None)
+        .await
+        .ok()?;
+    for device in dbus::strings(&devices.child_value(0)) {
+        let kind = dbus::property(
+            conn,
+            NM,
+            &device,
+            "org.freedesktop.NetworkManager.Device",
+            "DeviceType",
+        )
+        .await;
+        if kind.ok().and_then(|k| k.get::<u32>()) == Some(NM_DEVICE_TYPE_WIFI) {
+            return Some(device);
+        }
+    }
+    None
+}
+
+/// visible networks, one entry per ssid, strongest first
+async fn access_points(conn: &DBusConnection, device: &str) -> Vec<AccessPoint> {
+    let iface = "org.freedesktop.NetworkManager.Device.Wireless";
+    let _ = dbus::call(
+        conn,
+        NM,
+        device,
+        iface,
+        "RequestScan",
+        Some(&(HashMap::<String, Variant>::new(),).to_variant()),
+    )
+    .await;
+    let Ok(paths) = dbus::call(conn, NM, device, iface, "GetAllAccessPoints", None).await else {
+        return Vec::new();
+    };
+    let mut access_points: Vec<AccessPoint> = Vec::new();
+    for path in dbus::strings(&paths.child_value(0)) {
+        if let Ok(ap) = access_point(conn, &path).await {
+            if ap.ssid.is_empty() {
+                continue;
+            }
+            if let Some(known) = access_points.iter_mut().find(|a| a.ssid == ap.ssid) {
+                if known.strength < ap.strength {
+                    *known = ap;
+                }
+            } else {
+                access_points.push(ap);
+            }
+        }
+    }
+    access_points.sort_by_key(|ap| std::cmp::Reverse(ap.strength));
+    access_points
+}
+
+/// a saved connection profile for `ssid`
+async fn saved_connection(conn: &DBusConnection, ssid: &str) -> Option<String> {
+    let settings_iface = "org.freedesktop.NetworkManager.Settings";
+    let list = dbus::call(
+        conn,
+        NM,
+        NM_SETTINGS_PATH,
+        settings_iface,
+        "ListConnections",
+        None,
+    )
+    .await
+    .ok()?;
+    for path in dbus::strings(&list.child_value(0)) {
+        let Ok(settings) = dbus::call(
+            conn,
+            NM,
+            &path,
+            "org.freedesktop.NetworkManager.Settings.Connection",
+            "GetSettings",
+            None,
+        )
+        .await
+        else {
+            continue;
+        };
+        // a{sa{sv}}, setting name to its properties
+        for setting in settings.child_value(0).iter() {
+            if setting.child_value(0).str() == Some("802-11-wireless") {
+                let wireless = VariantDict::new(Some(&setting.child_value(1)));
+                if wireless.lookup::<Vec<u8>>("ssid").ok().flatten().as_deref()
+                    == Some(ssid.as_bytes())
+                {
+                    return Some(path);
+                }
+            }
+        }
+    }
+    None
+}
+
+async fn connect(conn: &DBusConnection, device: &str, ap: &AccessPoint) -> Result<()> {
+    if let Some(saved) = saved_connection(conn, &ap.ssid).await {
+        let params = (
+            object_path(&saved),
+            object_path(device),
+            object_path(&ap.path),
+        );
+        dbus::call(
+            conn,
+            NM,
+            NM_PATH,
+            NM,
+            "ActivateConnection",
+            Some(&params.to_variant()),
+        )
+        .await?;
+    } else {
+        // NetworkManager asks the secret agent for a password if it needs one
+        let params = (
+            HashMap::<String, HashMap<String, Variant>>::new(),
+            object_path(device),
+            object_path(&ap.path),
+        );
+        dbus::call(
+            conn,
+            NM,
+            NM_PATH,
+            NM,
+            "AddAndActivateConnection",
+            Some(&params.to_variant()),
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+async fn disconnect(conn: &DBusConnection, connection: &str) -> Result<()> {
+    dbus::call(
+        conn,
+        NM,
+        NM_PATH,
+        NM,
+        "DeactivateConnection",
+        Some(&(object_path(connection),).to_variant()),
+    )
+    .await?;
+    Ok(())
+}
+
+/// the interface the default route goes through
+pub fn default_interface() -> Option<String> {
+    let route = std::fs::read_to_string("/proc/net/route").unwrap_or_default();
+    route
+        .lines()
+        .skip(1)
+        .map(|l| l.split_whitespace().collect::<Vec<&str>>())
+        .find(|f| f.get(1) == Some(&"00000000"))
+        .map(|f| f[0].to_string())
+}
+
+fn sys_net(iface: &str, file: &str) -> String {
+    std::fs::read_to_string(format!("/sys/class/net/{iface}/{file}"))
+        .map(|s| s.trim().to_string())
+        .unwrap_or_default()
+}
+
+/// whether a wlan radio exists and isn't blocked by rfkill
+fn wifi_enabled() -> bool {
+    let Ok(radios) = std::fs::read_dir("/sys/class/rfkill") else {
+        return false;
+    };
+    radios.flatten().any(|r| {
+        let read = |f: &str| std::fs::read_to_string(r.path().join(f)).unwrap_or_default();
+        read("type").trim() == "wlan" && read("soft").trim() == "0" && read("hard").trim() == "0"
+    })
+}
+
+/// for setups without NetworkManager, the default route decides which interface to show
+/// and without one the first physical interface that's up
+fn proc_status() -> Status {
+    let mut status = Status {
+        wifi_enabled: wifi_enabled(),
+        ..Default::default()
+    };
+    let interfaces: Vec<String> = std::fs::read_dir("/sys/class/net")
+        .map(|i| {
+            i.flatten()
+                .map(|i| i.file_name().to_string_lossy().to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+    // tunnels don't know their carrier and report `unknown`
+    status.vpn = interfaces
+        .iter()
+        .find(|i| {
+            (i.starts_with("tun") || i.starts_with("wg")) && sys_net(i, "operstate") != "down"
+        })
+        .cloned();
+    let iface = default_interface().or_else(|| {
+        interfaces
+            .iter()
+            .filter(|i| std::path::Path::new(&format!("/sys/class/net/{i}/device")).exists())
+            .find(|i| sys_net(i, "operstate") == "up")
+            .cloned()
+    });
+    let Some(iface) = iface else {
+        return status;
+    };
+    // Inter-| sta-|   Quality        |
+    //  face | tus | link level noise |
+    //  wlan0: 0000   54.  -56.  -256
+    let wireless = std::fs::read_to_string("/proc/net/wireless").unwrap_or_default();
+    let quality = wireless
+        .lines()
+        .skip(2)
+        .filter_map(|l| l.split_once(":"))
+        .find(|(name, _)| name.trim() == iface)
+        .and_then(|(_, rest)| {
+            rest.split_whitespace()
+                .nth(1)?
+                .trim_end_matches('.')
+                .parse::<f32>()
+                .ok()
+        });
+    let is_wireless = std::path::Path::new(&format!("/sys/class/net/{iface}/wireless")).exists();
+    status.state = if quality.is_some() || is_wireless {
+        State::Wifi {
+            ssid: iface,
+            strength: (quality.unwrap_or(0.0) / 70.0 * 100.0).min(100.0) as u8,
+        }
+    } else {
+        State::Wired(iface)
+    };
+    status
+}
+
+#[derive(Clone)]
+struct Network {
+    widget: Box,
+    icon: Icon,
+    label: Label,
+    vpn: Icon,
+    menu: Popover,
+    wifi: Switch,
+    access_points: Box,
+    // set while the switch is moved to match NetworkManager so it doesn't write the state back
+    syncing: Rc<Cell<bool>>,
+}
+
+impl Network {
+    fn new() -> Self {
+        let widget = Box::new(Horizontal, 5);
+        widget.add_css_class("container");
+        widget.add_css_class("network");
+        let icon = Icon::new("network");
+        let label = Label::new(None);
+        let vpn = Icon::new("network");
+        vpn.set("󰖂", "network-vpn-symbolic");
+        vpn.widget.set_visible(false);
+        widget.append(&icon.widget);
+        widget.append(&label);
+        widget.append(&vpn.widget);
+        let menu = Popover::builder().has_arrow(false).build();
+        let content = Box::new(Vertical, 5);
+        let header = Box::new(Horizontal, 10);
+        let wifi = Switch::new();
+        let access_points = Box::new(Vertical, 0);
+        content.add_css_class("network-menu");
+        header.append(&Label::new(Some("Wi-Fi")));
+        header.append(&wifi);
+        content.append(&header);
+        content.append(&access_points);
+        menu.set_child(Some(&content));
+        menu.set_parent(&widget);
+        Self {
+            widget,
+            icon,
+            label,
+            vpn,
+            menu,
+            wifi,
+            access_points,
+            syncing: Rc::new(Cell::new(false)),
+        }
+    }
+    fn show(&self, status: &Status) {
+        let (kind, name, strength, glyph, icon_name) = match &status.state {
+            State::Disconnected => ("disconnected", "", 0, "󰤮", "network-offline-symbolic"),
+            State::Wired(name) => ("wired", name.as_str(), 100, "󰈀", "network-wired-symbolic"),
+            State::Wifi { ssid, strength } => (
+                "wifi",
+                ssid.as_str(),
+                *strength,
+                wifi_icon(*strength),
+                wifi_icon_name(*strength),
+            ),
+        };
+        // configured icons are glyphs or icon names, whichever the style uses
+        match CONFIG.network.icon(kind, strength as f64) {
+            Some(configured) => self.icon.set(configured, configured),
+            None => self.icon.set(glyph, icon_name),
+        }
+        let label = CONFIG.network.render(
+            "{name}",
+            &[
+                ("name", name.into()),
+                ("kind", kind.into()),
+                ("strength", (strength as f64).into()),
+            ],
+        );
+        self.label.set_text(&label);
+        self.label.set_visible(!label.trim().is_empty());
+        self.vpn.widget.set_visible(status.vpn.is_some());
+        let mut lines = vec![match &status.state {
+            State::Disconnected => "disconnected".to_string(),
+            State::Wired(name) => format!("<b>{}</b>  wired", tooltip::escape(name)),
+            State::Wifi { ssid, strength } => {
+                format!("<b>{}</b>  {strength}%", tooltip::escape(ssid))
+            }
+        }];
+        if let Some(vpn) = &status.vpn {
+            lines.push(tooltip::row("vpn", vpn));
+        }
+        tooltip::set(&self.widget, &lines.join("\n"));
+        self.syncing.set(true);
+        self.wifi.set_active(status.wifi_enabled);
+        self.syncing.set(false);
+    }
+}
+
+async fn listen_nm(network: Network, conn: DBusConnection) {
+    let refresh = clone! {
+        #[strong] network,
+        #[strong] conn,
+        move || {
+            spawn_future_local(clone! {
+                #[strong] network,
+                #[strong] conn,
+                async move {
+                    if let Ok(status) = status(&conn).await {
+                        network.show(&status);
+                    }
+                }
+            });
+        }
+    };
+    refresh();
+    // access points change strength all the time so updates are batched
+    let pending = Rc::new(Cell::new(false));
+    dbus::on_properties_changed(&conn, NM, None, move |_, _, _| {
+        if !pending.get() {
+            pending.set(true);
+            spawn_future_local(clone! {
+                #[strong] pending,
+                #[strong] refresh,
+                async move {
+                    sleep(Duration::from_millis(500)).await;
+                    pending.set(false);
+                    refresh();
+                }
+            });
+        }
+    });
+    network.wifi.connect_state_set(clone! {
+        #[strong] conn,
+        #[strong(rename_to = syncing)] network.syncing,
+        move |_, enabled| {
+            if syncing.get() {
+                return Propagation::Proceed;
+            }
+            spawn_future_local(clone! {
+                #[strong] conn,
+                async move {
+                    let enabled = enabled.to_variant();
+                    let _ = dbus::set_property(&conn, NM, NM_PATH, NM, "WirelessEnabled", enabled)
+                        .await;
+                }
+            });
+            Propagation::Proceed
+        }
+    });
+    let click = GestureClick::new();
+    click.connect_pressed(clone! {
+        #[strong] network,
+        #[strong] conn,
+        move |_, _, _, _| {
+            network.menu.popup();
+            spawn_future_local(clone! {
+                #[strong] network,
+                #[strong] conn,
+                async move {
+                    let Some(device) = wifi_device(&conn).await else {
+                        return;
+                    };
+                    let current = status(&conn).await.unwrap_or_default();
+                    while let Some(child) = network.access_points.first_child() {
+                        network.access_points.remove(&child);
+                    }
+                    for ap in access_points(&conn, &device).await {
+                        let connected = matches!(
+                            &current.state,
+                            State::Wifi { ssid, .. } if *ssid == ap.ssid
+                        );
+                        let content = Box::new(Horizontal, 5);
+                        let strength = Icon::new("network");
+                        strength.set(wifi_icon(ap.strength), wifi_icon_name(ap.strength));
+                        content.append(&strength.widget);
+                        content.append(&Label::new(Some(&ap.ssid)));
+                        if ap.secure {
+                            let lock = Icon::new("network");
+                            lock.set("󰌾", "network-wireless-encrypted-symbolic");
+                            content.append(&lock.widget);
+                        }
+                        let button = Button::builder().child(&content).build();
+                        if connected {
+                            button.add_css_class("active");
+                        }
+                        let connection = current.wifi_connection.clone();
+                        button.connect_clicked(clone! {
+                            #[strong] conn,
+                            #[strong] device,
+                            #[weak(rename_to = menu)] network.menu,
+                            move |_| {
+                                spawn_future_local(clone! {
+                                    #[strong] conn,
+                                    #[strong] device,
+                                    #[strong] ap,
+                                    #[strong] connection,
+                                    async move {
+                                        let result = match (&connection, connected) {
+                                            (Some(connection), true) => {
+                                                disconnect(&conn, connection).await
+                                            }
+                                            _ => connect(&conn, &device, &ap).await,
+                                        };
+                                        if let Err(e) = result {
+                                            eprintln!("network: {e}");
+                                        }
+                                    }
+                                });
+                                menu.popdown();
+                            }
+                        });
+                        network.access_points.append(&button);
+                    }
+                }
+            });
+        }
+    });
+    network.widget.add_controller(click);
+}
+
+pub fn new() -> Box {
+    let network = Network::new();
+    spawn_future_local(clone! {
+        #[strong] network,
+        async move {
+            // fall back to /proc when NetworkManager isn't around
+            if let Ok(conn) = dbus::system().await {
+                if dbus::properties(&conn, NM, NM_PATH, NM).await.is_ok() {
+                    listen_nm(network, conn).await;
+                    return;
+                }
+            }
+            network.wifi.set_sensitive(false);
+            loop {
+                network.show(&proc_status());
+                sleep(Duration::from_secs(5)).await;
+            }
+        }
+    });
+    network.widget
+}
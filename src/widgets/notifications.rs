@@ -0,0 +1,140 @@
+use crate::*;
+use gtk::{Popover, ScrolledWindow, Switch};
+use libs::icons::Icon;
+use libs::notifications::{Daemon, Event};
+use libs::tooltip;
+use windows::popup_notifications::card;
+
+#[derive(Clone)]
+struct Center {
+    widget: Box,
+    icon: Icon,
+    count: Label,
+    menu: Popover,
+    dnd: Switch,
+    clear: Button,
+    list: Box,
+}
+
+impl Center {
+    fn new() -> Self {
+        let widget = Box::new(Horizontal, 5);
+        widget.add_css_class("container");
+        widget.add_css_class("notifications");
+        let icon = Icon::new("notifications");
+        let count = Label::new(None);
+        widget.append(&icon.widget);
+        widget.append(&count);
+        let menu = Popover::builder().has_arrow(false).build();
+        let content = Box::new(Vertical, 5);
+        content.add_css_class("notification-center");
+        let header = Box::new(Horizontal, 10);
+        let title = Label::new(Some("Notifications"));
+        title.set_hexpand(true);
+        title.set_xalign(0.0);
+        let dnd = Switch::new();
+        dnd.set_tooltip_text(Some("Do not disturb"));
+        let clear = Button::with_label("Clear");
+        header.append(&title);
+        let dnd_icon = Icon::new("notifications");
+        dnd_icon.set("󰂛", "notifications-disabled-symbolic");
+        header.append(&dnd_icon.widget);
+        header.append(&dnd);
+        header.append(&clear);
+        let list = Box::new(Vertical, 10);
+        let scroll = ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Never)
+            .propagate_natural_height(true)
+            .max_content_height(600)
+            .min_content_width(360)
+            .child(&list)
+            .build();
+        content.append(&header);
+        content.append(&scroll);
+        menu.set_child(Some(&content));
+        menu.set_parent(&widget);
+        Self {
+            widget,
+            icon,
+            count,
+            menu,
+            dnd,
+            clear,
+            list,
+        }
+    }
+    fn show(&self, daemon: &Daemon) {
+        let unread = daemon.unread();
+        let (glyph, name) = match (daemon.dnd(), unread) {
+            (true, _) => ("󰂛", "notifications-disabled-symbolic"),
+            (false, 0) => ("󰂜", "preferences-system-notifications-symbolic"),
+            (false, _) => ("󰂚", "notification-new-symbolic"),
+        };
+        self.icon.set(glyph, name);
+        self.count.set_text(&unread.to_string());
+        self.count.set_visible(unread > 0);
+        tooltip::set(
+            &self.widget,
+            &format!(
+                "{}\n{}",
+                tooltip::row("unread", &unread.to_string()),
+                tooltip::row("do not disturb", if daemon.dnd() { "on" } else { "off" }),
+            ),
+        );
+        if self.dnd.is_active() != daemon.dnd() {
+            self.dnd.set_active(daemon.dnd());
+        }
+        while let Some(child) = self.list.first_child() {
+            self.list.remove(&child);
+        }
+        let history = daemon.history();
+        if history.is_empty() {
+            self.list.append(&Label::new(Some("No notifications")));
+        }
+        for notification in &history {
+            self.list.append(&card(daemon, notification, true));
+        }
+    }
+}
+
+pub fn new(daemon: &Daemon) -> Box {
+    let center = Center::new();
+    center.show(daemon);
+    center.dnd.connect_state_set(clone! {
+        #[strong] daemon,
+        move |_, dnd| {
+            if daemon.dnd() != dnd {
+                daemon.set_dnd(dnd);
+            }
+            Propagation::Proceed
+        }
+    });
+    center.clear.connect_clicked(clone! {
+        #[strong] daemon,
+        move |_| daemon.clear()
+    });
+    // opening the center counts as having read everything
+    center.menu.connect_show(clone! {
+        #[strong] daemon,
+        move |_| daemon.mark_read()
+    });
+    let click = GestureClick::new();
+    click.connect_pressed(clone! {
+        #[strong] center,
+        move |_, _, _, _| center.menu.popup()
+    });
+    center.widget.add_controller(click);
+    let mut listener = daemon.listener();
+    spawn_future_local(clone! {
+        #[strong] center,
+        #[strong] daemon,
+        async move {
+            while let Ok(event) = listener.recv().await {
+                if let Event::Changed = event {
+                    center.show(&daemon);
+                }
+            }
+        }
+    });
+    center.widget
+}
@@ -0,0 +1,207 @@
+use crate::*;
+use anyhow::Result;
+use gtk::{Popover, Stack};
+use libs::icons::Icon;
+use libs::{dbus, process, tooltip};
+use std::cell::Cell;
+use std::rc::Rc;
+
+const LOGIN1: &str = "org.freedesktop.login1";
+const LOGIN1_PATH: &str = "/org/freedesktop/login1";
+
+#[derive(Clone, Copy)]
+enum Action {
+    Lock,
+    Logout,
+    Suspend,
+    Hibernate,
+    Reboot,
+    Shutdown,
+}
+
+impl Action {
+    const ALL: [Self; 6] = [
+        Self::Lock,
+        Self::Logout,
+        Self::Suspend,
+        Self::Hibernate,
+        Self::Reboot,
+        Self::Shutdown,
+    ];
+    fn label(self) -> &'static str {
+        match self {
+            Self::Lock => "Lock",
+            Self::Logout => "Logout",
+            Self::Suspend => "Suspend",
+            Self::Hibernate => "Hibernate",
+            Self::Reboot => "Reboot",
+            Self::Shutdown => "Shutdown",
+        }
+    }
+    /// the glyph and the theme icon name
+    fn icon(self) -> (&'static str, &'static str) {
+        match self {
+            Self::Lock => ("󰌾", "system-lock-screen-symbolic"),
+            Self::Logout => ("󰍃", "system-log-out-symbolic"),
+            Self::Suspend => ("󰒲", "system-suspend-symbolic"),
+            Self::Hibernate => ("󰋊", "system-hibernate-symbolic"),
+            Self::Reboot => ("󰜉", "system-reboot-symbolic"),
+            Self::Shutdown => ("󰐥", "system-shutdown-symbolic"),
+        }
+    }
+    /// locking is harmless, everything else is worth a second look
+    fn needs_confirmation(self) -> bool {
+        CONFIG.power.confirm && !matches!(self, Self::Lock)
+    }
+    async fn run(self) -> Result<()> {
+        // logind's manager methods take an `interactive` flag for polkit prompts
+        let manager = |method: &'static str| async move {
+            let conn = dbus::system().await?;
+            dbus::call(
+                &conn,
+                LOGIN1,
+                LOGIN1_PATH,
+                "org.freedesktop.login1.Manager",
+                method,
+                Some(&(true,).to_variant()),
+            )
+            .await?;
+            anyhow::Ok(())
+        };
+        match self {
+            Self::Lock => match &CONFIG.power.lock_command {
+                Some(command) => process::sh(command)?,
+                None => {
+                    let conn = dbus::system().await?;
+                    dbus::call(
+                        &conn,
+                        LOGIN1,
+                        "/org/freedesktop/login1/session/auto",
+                        "org.freedesktop.login1.Session",
+                        "Lock",
+                        None,
+                    )
+                    .await?;
+                }
+            },
+            Self::Logout => {
+                hyprland::ctl("dispatch exit");
+            }
+            Self::Suspend => manager("Suspend").await?,
+            Self::Hibernate => manager("Hibernate").await?,
+            Self::Reboot => manager("Reboot").await?,
+            Self::Shutdown => manager("PowerOff").await?,
+        }
+        Ok(())
+    }
+}
+
+/// time since boot from `/proc/uptime`
+fn uptime() -> Option<String> {
+    let uptime = std::fs::read_to_string("/proc/uptime").ok()?;
+    let seconds = uptime.split_whitespace().next()?.parse::<f64>().ok()? as u64;
+    let (days, hours, minutes) = (seconds / 86400, seconds / 3600 % 24, seconds / 60 % 60);
+    Some(match days {
+        0 => format!("{hours}h {minutes:02}m"),
+        _ => format!("{days}d {hours}h {minutes:02}m"),
+    })
+}
+
+fn run(action: Action) {
+    spawn_future_local(async move {
+        if let Err(e) = action.run().await {
+            eprintln!("power: {}: {e}", action.label());
+        }
+    });
+}
+
+pub fn new() -> Box {
+    let widget = Box::new(Horizontal, 0);
+    widget.add_css_class("container");
+    widget.add_css_class("power");
+    let icon = Icon::new("power");
+    icon.set("󰐥", "system-shutdown-symbolic");
+    widget.append(&icon.widget);
+    tooltip::lazy(&widget, || Some(tooltip::row("uptime", &uptime()?)));
+    let menu = Popover::builder().has_arrow(false).build();
+    let stack = Stack::new();
+    stack.add_css_class("power-menu");
+    let actions = Box::new(Vertical, 0);
+    let confirm = Box::new(Vertical, 5);
+    let question = Label::new(None);
+    let buttons = Box::new(Horizontal, 5);
+    let yes = Button::with_label("Yes");
+    let no = Button::with_label("Cancel");
+    yes.set_hexpand(true);
+    no.set_hexpand(true);
+    buttons.append(&no);
+    buttons.append(&yes);
+    confirm.append(&question);
+    confirm.append(&buttons);
+    stack.add_named(&actions, Some("actions"));
+    stack.add_named(&confirm, Some("confirm"));
+    menu.set_child(Some(&stack));
+    menu.set_parent(&widget);
+
+    // the action the confirmation is about
+    let pending = Rc::new(Cell::new(None::<Action>));
+    for action in Action::ALL {
+        let content = Box::new(Horizontal, 5);
+        let icon = Icon::new("power_menu");
+        let (glyph, name) = action.icon();
+        icon.set(glyph, name);
+        content.append(&icon.widget);
+        content.append(&Label::new(Some(action.label())));
+        let button = Button::builder().child(&content).build();
+        button.connect_clicked(clone! {
+            #[weak] menu,
+            #[weak] stack,
+            #[weak] question,
+            #[strong] pending,
+            move |_| {
+                if action.needs_confirmation() {
+                    pending.set(Some(action));
+                    question.set_text(&format!("{}?", action.label()));
+                    stack.set_visible_child_name("confirm");
+                } else {
+                    menu.popdown();
+                    run(action);
+                }
+            }
+        });
+        actions.append(&button);
+    }
+    yes.connect_clicked(clone! {
+        #[weak] menu,
+        #[strong] pending,
+        move |_| {
+            // closing the menu forgets the pending action, so take it first
+            let action = pending.take();
+            menu.popdown();
+            if let Some(action) = action {
+                run(action);
+            }
+        }
+    });
+    no.connect_clicked(clone! {
+        #[weak] stack,
+        #[strong] pending,
+        move |_| {
+            pending.set(None);
+            stack.set_visible_child_name("actions");
+        }
+    });
+    // always start from the list
+    menu.connect_closed(clone! {
+        #[weak] stack,
+        #[strong] pending,
+        move |_| {
+            pending.set(None);
+            stack.set_visible_child_name("actions");
+        }
+    });
+    let click = GestureClick::new();
+    click.connect_pressed(move |_, _, _, _| menu.popup());
+    widget.add_controller(click);
+    widget
+}
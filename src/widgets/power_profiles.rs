@@ -0,0 +1,167 @@
+use crate::*;
+use anyhow::Result;
+use gio::DBusConnection;
+use libs::icons::Icon;
+use libs::{dbus, tooltip};
+use std::rc::Rc;
+
+/// newer power-profiles-daemon lives under upower, older ones under hadess
+const SERVICES: [(&str, &str); 2] = [
+    (
+        "org.freedesktop.UPower.PowerProfiles",
+        "/org/freedesktop/UPower/PowerProfiles",
+    ),
+    ("net.hadess.PowerProfiles", "/net/hadess/PowerProfiles"),
+];
+
+#[derive(Clone)]
+struct Service {
+    conn: DBusConnection,
+    name: &'static str,
+    path: &'static str,
+}
+
+impl Service {
+    async fn find() -> Option<Self> {
+        let conn = dbus::system().await.ok()?;
+        for (name, path) in SERVICES {
+            if dbus::properties(&conn, name, path, name).await.is_ok() {
+                return Some(Self { conn, name, path });
+            }
+        }
+        None
+    }
+    /// the active profile and the available ones in the daemon's order
+    async fn profiles(&self) -> Result<(String, Vec<String>)> {
+        let props = dbus::properties(&self.conn, self.name, self.path, self.name).await?;
+        let active = props.lookup::<String>("ActiveProfile")?.unwrap_or_default();
+        // aa{sv}, one dict per profile
+        let profiles = props
+            .lookup_value("Profiles", None)
+            .map(|p| {
+                p.iter()
+                    .filter_map(|p| {
+                        glib::VariantDict::new(Some(&p))
+                            .lookup::<String>("Profile")
+                            .ok()?
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        Ok((active, profiles))
+    }
+    async fn set(&self, profile: &str) -> Result<()> {
+        dbus::set_property(
+            &self.conn,
+            self.name,
+            self.path,
+            self.name,
+            "ActiveProfile",
+            profile.to_variant(),
+        )
+        .await
+    }
+}
+
+/// the glyph and the theme icon name of `profile`
+pub fn icon(profile: &str) -> (&'static str, &'static str) {
+    match profile {
+        "power-saver" => ("󰌪", "power-profile-power-saver-symbolic"),
+        "performance" => ("󰓅", "power-profile-performance-symbolic"),
+        _ => ("󰾅", "power-profile-balanced-symbolic"),
+    }
+}
+
+/// calls `f` with the active profile now and whenever it changes, does nothing without the daemon
+pub fn watch<F>(f: F)
+where
+    F: Fn(&str) + 'static,
+{
+    spawn_future_local(async move {
+        let Some(service) = Service::find().await else {
+            return;
+        };
+        let f = Rc::new(f);
+        let refresh = clone! {
+            #[strong] service,
+            #[strong] f,
+            move || {
+                spawn_future_local(clone! {
+                    #[strong] service,
+                    #[strong] f,
+                    async move {
+                        if let Ok((active, _)) = service.profiles().await {
+                            f(&active);
+                        }
+                    }
+                });
+            }
+        };
+        refresh();
+        dbus::on_properties_changed(
+            &service.conn,
+            service.name,
+            Some(service.path),
+            move |_, _, changed| {
+                if changed.contains("ActiveProfile") {
+                    refresh();
+                }
+            },
+        );
+    });
+}
+
+pub fn new() -> Box {
+    let widget = Box::new(Horizontal, 5);
+    widget.add_css_class("container");
+    widget.add_css_class("power-profile");
+    widget.set_visible(false);
+    let icon = Icon::new("power_profiles");
+    let label = Label::new(None);
+    widget.append(&icon.widget);
+    widget.append(&label);
+    watch(clone! {
+        #[weak] widget,
+        move |active: &str| {
+            widget.set_visible(true);
+            for profile in ["power-saver", "balanced", "performance"] {
+                widget.remove_css_class(profile);
+            }
+            widget.add_css_class(active);
+            let (glyph, name) = self::icon(active);
+            icon.set(glyph, name);
+            label.set_text(active);
+            tooltip::set(
+                &widget,
+                &format!("{}\n<i>click to switch</i>", tooltip::row("profile", active)),
+            );
+        }
+    });
+    let click = GestureClick::new();
+    click.connect_pressed(|_, _, _, _| {
+        spawn_future_local(async {
+            let Some(service) = Service::find().await else {
+                return;
+            };
+            let result = match service.profiles().await {
+                Ok((active, profiles)) => {
+                    // cycle in the order the daemon lists them, power-saver to performance
+                    let next = profiles
+                        .iter()
+                        .position(|p| *p == active)
+                        .map_or(0, |i| (i + 1) % profiles.len().max(1));
+                    match profiles.get(next) {
+                        Some(next) => service.set(next).await,
+                        None => Ok(()),
+                    }
+                }
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                eprintln!("power profiles: {e}");
+            }
+        });
+    });
+    widget.add_controller(click);
+    widget
+}
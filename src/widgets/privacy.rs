@@ -0,0 +1,214 @@
+use crate::*;
+use libs::icons::Icon;
+use libs::pulse::Pulse;
+use libs::{process, tooltip};
+use pulseaudio::protocol::{
+    command::{SubscriptionEvent, SubscriptionEventFacility},
+    Prop,
+};
+use serde_json::Value;
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::fs::{read_dir, read_link, read_to_string};
+use std::rc::Rc;
+
+#[derive(Deserialize)]
+struct PwObject {
+    #[serde(default)]
+    info: Option<PwInfo>,
+}
+
+#[derive(Deserialize)]
+struct PwInfo {
+    #[serde(default)]
+    state: Option<String>,
+    #[serde(default)]
+    props: HashMap<String, Value>,
+}
+
+/// apps with a running video capture stream, what a screen share looks like to pipewire
+async fn screen_sharers() -> Vec<String> {
+    let Ok(output) = process::output(&["pw-dump"]).await else {
+        return Vec::new();
+    };
+    let objects: Vec<PwObject> = from_str(&output).unwrap_or_default();
+    objects
+        .into_iter()
+        .filter_map(|o| o.info)
+        .filter(|i| i.state.as_deref() == Some("running"))
+        .filter(|i| {
+            i.props.get("media.class").and_then(Value::as_str) == Some("Stream/Input/Video")
+        })
+        .filter_map(|i| {
+            ["application.name", "node.name"]
+                .iter()
+                .find_map(|k| i.props.get(*k).and_then(Value::as_str).map(str::to_string))
+        })
+        .collect()
+}
+
+/// processes holding a `/dev/video*` device open, this walks all of `/proc` so it runs
+/// off the main thread
+fn camera_users() -> Vec<String> {
+    let mut users = BTreeSet::new();
+    let Ok(processes) = read_dir("/proc") else {
+        return Vec::new();
+    };
+    for process in processes.flatten() {
+        let Ok(fds) = read_dir(process.path().join("fd")) else {
+            continue;
+        };
+        let camera = fds
+            .flatten()
+            .any(|fd| read_link(fd.path()).is_ok_and(|target| target.starts_with("/dev/video")));
+        if camera {
+            if let Ok(comm) = read_to_string(process.path().join("comm")) {
+                users.insert(comm.trim().to_string());
+            }
+        }
+    }
+    users.into_iter().collect()
+}
+
+/// apps with an uncorked recording stream
+fn microphone_users(pulse: &Pulse) -> Vec<String> {
+    let Ok(outputs) = pulse.get_source_outputs() else {
+        return Vec::new();
+    };
+    let mut users = BTreeSet::new();
+    for output in outputs.iter().filter(|o| !o.corked) {
+        // pavucontrol's level meters record too
+        let prop = |p| {
+            output.props.get(p).map(|v| {
+                String::from_utf8_lossy(v)
+                    .trim_end_matches('\0')
+                    .to_string()
+            })
+        };
+        if prop(Prop::ApplicationId).as_deref() == Some("org.PulseAudio.pavucontrol") {
+            continue;
+        }
+        users.insert(
+            prop(Prop::ApplicationName)
+                .unwrap_or_else(|| output.name.to_string_lossy().to_string()),
+        );
+    }
+    users.into_iter().collect()
+}
+
+#[derive(Clone)]
+struct Privacy {
+    widget: Box,
+    screen: Icon,
+    camera: Icon,
+    microphone: Icon,
+    /// who is using what, for the tooltip
+    users: Rc<RefCell<[Vec<String>; 3]>>,
+}
+
+impl Privacy {
+    fn show(&self) {
+        let users = self.users.borrow();
+        let indicators = [&self.screen, &self.camera, &self.microphone];
+        for (indicator, users) in indicators.iter().zip(users.iter()) {
+            indicator.widget.set_visible(!users.is_empty());
+        }
+        self.widget.set_visible(users.iter().any(|u| !u.is_empty()));
+        let tooltip = ["screen", "camera", "microphone"]
+            .iter()
+            .zip(users.iter())
+            .filter(|(_, users)| !users.is_empty())
+            .map(|(kind, users)| tooltip::row(kind, &users.join(", ")))
+            .collect::<Vec<String>>()
+            .join("\n");
+        tooltip::set(&self.widget, &tooltip);
+    }
+    fn set(&self, i: usize, users: Vec<String>) {
+        if self.users.borrow()[i] != users {
+            self.users.borrow_mut()[i] = users;
+            self.show();
+        }
+    }
+}
+
+pub fn new(event_listener: Receiver<String>) -> Box {
+    let widget = Box::new(Horizontal, 5);
+    widget.add_css_class("container");
+    widget.add_css_class("privacy");
+    let indicator = |class: &str, glyph: &str, name: &str| {
+        let icon = Icon::new("privacy");
+        icon.set(glyph, name);
+        icon.widget.add_css_class(class);
+        widget.append(&icon.widget);
+        icon
+    };
+    let screen = indicator("screen", "󰹑", "video-display-symbolic");
+    let camera = indicator("camera", "󰄀", "camera-web-symbolic");
+    let microphone = indicator("microphone", "󰍬", "audio-input-microphone-symbolic");
+    let privacy = Privacy {
+        widget,
+        screen,
+        camera,
+        microphone,
+        users: Rc::new(RefCell::new(Default::default())),
+    };
+    privacy.show();
+
+    // hyprland says when screencopy starts and stops, pipewire knows who asked for it,
+    // a video stream alone could just as well be a camera so nothing counts before the event
+    spawn_future_local(clone! {
+        #[strong] privacy,
+        async move {
+            let mut event_listener = event_listener;
+            loop {
+                if let Ok(event) = event_listener.recv().await {
+                    if let Some(data) = event.strip_prefix("screencast>>") {
+                        let mut users = Vec::new();
+                        if data.starts_with('1') {
+                            users = screen_sharers().await;
+                            if users.is_empty() {
+                                users.push("unknown".to_string());
+                            }
+                        }
+                        privacy.set(0, users);
+                    }
+                }
+            }
+        }
+    });
+
+    // nothing tells us when a camera is opened, so look every few seconds
+    spawn_future_local(clone! {
+        #[strong] privacy,
+        async move {
+            loop {
+                privacy.set(1, gio::spawn_blocking(camera_users).await.unwrap_or_default());
+                sleep(Duration::from_secs(3)).await;
+            }
+        }
+    });
+
+    if let (Ok(pulse_event), Ok(pulse_info)) = (
+        Pulse::new("z3phyrl.gtk-bar.privacy.event"),
+        Pulse::new("z3phyrl.gtk-bar.privacy.info"),
+    ) {
+        if let Ok(subscription) = pulse_event.subscribe() {
+            spawn_future_local(clone! {
+                #[strong] privacy,
+                async move {
+                    // the event connection has to stay open for the subscription
+                    let _pulse_event = pulse_event;
+                    privacy.set(2, microphone_users(&pulse_info));
+                    while let Ok(SubscriptionEvent { event_facility, .. }) =
+                        subscription.recv().await
+                    {
+                        if matches!(event_facility, SubscriptionEventFacility::SourceOutput) {
+                            privacy.set(2, microphone_users(&pulse_info));
+                        }
+                    }
+                }
+            });
+        }
+    }
+    privacy.widget
+}
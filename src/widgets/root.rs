@@ -2,17 +2,86 @@ use crate::*;
 use async_broadcast::{broadcast, InactiveReceiver, Receiver};
 use hyprland::ctl;
 
+use libs::animation::{self, Transition};
+use libs::config::BackgroundMode;
+use libs::shared_widget::orientation;
+use serde_json::Value;
+use std::cell::Cell;
+use std::rc::Rc;
+
 #[derive(Deserialize, PartialEq)]
 struct WorkspaceInfo {
     id: i32,
     name: String,
 }
 
+#[derive(Deserialize)]
+struct MonitorInfo {
+    name: String,
+    #[serde(rename = "activeWorkspace")]
+    active_workspace: WorkspaceInfo,
+}
+
 #[derive(Deserialize)]
 struct Client {
     workspace: WorkspaceInfo,
     hidden: bool,
     floating: bool,
+    class: String,
+    // a bool on older hyprland, the fullscreen mode on newer ones
+    fullscreen: Value,
+}
+
+impl Client {
+    fn fullscreen(&self) -> bool {
+        match &self.fullscreen {
+            Value::Bool(fullscreen) => *fullscreen,
+            Value::Number(mode) => mode.as_u64() != Some(0),
+            _ => false,
+        }
+    }
+}
+
+/// the workspace the bar sits over, either the focused one or the one on the bar's monitor
+fn current_workspace(event: Option<&str>) -> WorkspaceInfo {
+    if CONFIG.background.per_monitor {
+        if let Some(name) = &CONFIG.monitor {
+            if let Some(monitor) = from_str::<Vec<MonitorInfo>>(&ctl("j/monitors"))
+                .unwrap_or_default()
+                .into_iter()
+                .find(|m| m.name == *name)
+            {
+                return monitor.active_workspace;
+            }
+        }
+    } else if let Some(data) = event {
+        let data: Vec<&str> = data.split(",").collect();
+        if let Ok(id) = data[0].parse::<i32>() {
+            return WorkspaceInfo {
+                id,
+                name: data.get(1).unwrap_or(&"").to_string(),
+            };
+        }
+    }
+    from_str(&ctl("j/activeworkspace")).unwrap()
+}
+
+fn opaque(workspace: &WorkspaceInfo) -> bool {
+    let config = &CONFIG.background;
+    if config.mode == BackgroundMode::Opaque {
+        return true;
+    } else if config.mode == BackgroundMode::Transparent {
+        return false;
+    }
+    let clients: Vec<Client> = from_str(&ctl("j/clients")).unwrap_or_default();
+    let mut clients = clients.iter().filter(|c| {
+        c.workspace.id == workspace.id && !c.hidden && !config.ignore_classes.contains(&c.class)
+    });
+    if config.mode == BackgroundMode::Fullscreen {
+        clients.any(|c| c.fullscreen())
+    } else {
+        clients.any(|c| config.count_floating || !c.floating)
+    }
 }
 
 #[derive(Clone)]
@@ -33,18 +102,21 @@ impl Root {
     where
         W: IsA<Widget>,
     {
+        adapt(widget);
         self.left.append(widget);
     }
     pub fn center<W>(&mut self, widget: &W)
     where
         W: IsA<Widget>,
     {
+        adapt(widget);
         self.center.append(widget);
     }
     pub fn right<W>(&mut self, widget: &W)
     where
         W: IsA<Widget>,
     {
+        adapt(widget);
         self.right.append(widget);
     }
     pub fn transparent(&mut self, transparent: bool) {
@@ -63,24 +135,65 @@ impl Root {
     }
 }
 
+/// `opaque` or `transparent` on the `.bar` window the overlay ends up in
+fn mark(root: &Overlay, opaque: bool) {
+    let Some(window) = root.root() else {
+        return;
+    };
+    let (add, remove) = if opaque {
+        ("opaque", "transparent")
+    } else {
+        ("transparent", "opaque")
+    };
+    window.remove_css_class(remove);
+    window.add_css_class(add);
+}
+
+/// lay a module out along the bar, modules are built horizontally
+fn adapt<W>(widget: &W)
+where
+    W: IsA<Widget>,
+{
+    if let Some(widget) = widget.dynamic_cast_ref::<gtk::Orientable>() {
+        widget.set_orientation(orientation());
+    }
+}
+
 pub fn new(mut event_listener: Receiver<String>) -> Root {
-    let bg = Revealer::builder()
-        .transition_type(Crossfade)
-        .transition_duration(500)
-        .child(&Box::builder().css_classes(["bg"]).build())
-        .build();
+    let bg = animation::revealer(Transition::Fade, CONFIG.animation.durations.background);
+    bg.set_child(Some(&Box::builder().css_classes(["bg"]).build()));
     let background = bg.clone();
     let root = Overlay::builder().child(&bg).build();
-    let left = Box::new(Horizontal, 0);
-    let center = Box::new(Horizontal, 0);
-    let right = Box::new(Horizontal, 0);
+    let left = Box::new(orientation(), 0);
+    let center = Box::new(orientation(), 0);
+    let right = Box::new(orientation(), 0);
     let content = CenterBox::builder()
+        .orientation(orientation())
         .start_widget(&left)
         .center_widget(&center)
         .end_widget(&right)
         .build();
     root.add_overlay(&content);
     let (snd, recv) = broadcast(64);
+    // the overlay only gets its window later, so the state is applied again once it's there
+    let state = Rc::new(Cell::new(None::<bool>));
+    root.connect_realize(clone! {
+        #[strong] state,
+        move |root| {
+            if let Some(opaque) = state.get() {
+                mark(root, opaque);
+            }
+        }
+    });
+    let update = clone! {
+        #[strong] root,
+        move |opaque: bool| {
+            background.set_reveal_child(opaque);
+            state.set(Some(opaque));
+            mark(&root, opaque);
+        }
+    };
+    update(opaque(&current_workspace(None)));
     spawn_future_local(async move {
         loop {
             if let Ok(event) = event_listener.recv().await {
@@ -88,37 +201,20 @@ pub fn new(mut event_listener: Receiver<String>) -> Root {
                 let name = event.next();
                 match name {
                     Some("workspacev2")
+                    | Some("focusedmonv2")
                     | Some("openwindow")
                     | Some("closewindow")
+                    | Some("movewindowv2")
+                    | Some("fullscreen")
                     | Some("changefloatingmode") => {
-                        let clients: Vec<Client> = from_str(&ctl("j/clients")).unwrap();
-                        let current_workspace = if name == Some("workspacev2") {
-                            if let Some(data) = event.next() {
-                                let data: Vec<&str> = data.split(",").collect();
-                                WorkspaceInfo {
-                                    id: data[0].parse::<i32>().unwrap(),
-                                    name: data[1].to_string(),
-                                }
-                            } else {
-                                from_str(&ctl("j/activeworkspace")).unwrap()
-                            }
+                        let workspace = if name == Some("workspacev2") {
+                            current_workspace(event.next())
                         } else {
-                            from_str(&ctl("j/activeworkspace")).unwrap()
+                            current_workspace(None)
                         };
-                        if clients
-                            .iter()
-                            .filter(|c| {
-                                c.workspace == current_workspace && !c.hidden && !c.floating
-                            })
-                            .count()
-                            == 0
-                        {
-                            background.set_reveal_child(false);
-                            snd.broadcast(false).await.unwrap();
-                        } else {
-                            background.set_reveal_child(true);
-                            snd.broadcast(true).await.unwrap();
-                        }
+                        let opaque = opaque(&workspace);
+                        update(opaque);
+                        snd.broadcast(opaque).await.unwrap();
                     }
                     _ => {}
                 }
@@ -0,0 +1,37 @@
+use crate::*;
+use async_broadcast::Receiver;
+use hyprland::ctl;
+use libs::tooltip;
+
+fn is_default(submap: &str) -> bool {
+    matches!(submap, "" | "default" | "reset")
+}
+
+pub fn new(mut event_listener: Receiver<String>) -> Box {
+    let widget = Box::new(Horizontal, 5);
+    widget.add_css_class("container");
+    widget.add_css_class("submap");
+    let label = Label::new(None);
+    widget.append(&label);
+    let current = ctl("submap");
+    let current = current.trim();
+    label.set_text(current);
+    tooltip::set(&widget, &tooltip::row("submap", current));
+    // older hyprland doesn't know the request and answers with an error instead
+    widget.set_visible(!is_default(current) && !current.starts_with("unknown"));
+    spawn_future_local(clone! {
+        #[strong] widget,
+        async move {
+            loop {
+                if let Ok(event) = event_listener.recv().await {
+                    if let Some(("submap", submap)) = event.split_once(">>") {
+                        label.set_text(submap);
+                        tooltip::set(&widget, &tooltip::row("submap", submap));
+                        widget.set_visible(!is_default(submap));
+                    }
+                }
+            }
+        }
+    });
+    widget
+}
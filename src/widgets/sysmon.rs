@@ -0,0 +1,336 @@
+use crate::*;
+use gtk::LevelBar;
+use libs::config::Resource;
+use libs::format::Value;
+use libs::icons::Icon;
+use libs::shared_widget::Sparkline;
+use libs::tooltip;
+
+/// anything faster mostly measures the timer, and every polling module runs on the main thread
+pub const MIN_INTERVAL: u64 = 100;
+
+/// the configured milliseconds between updates, never below `MIN_INTERVAL`
+pub fn interval(millis: u64) -> Duration {
+    Duration::from_millis(millis.max(MIN_INTERVAL))
+}
+
+/// busy and total jiffies of a `cpu` line from `/proc/stat`
+#[derive(Clone, Copy, Default)]
+struct CpuTime {
+    busy: u64,
+    total: u64,
+}
+
+impl CpuTime {
+    /// usage in percent since `last`
+    fn usage(self, last: CpuTime) -> f64 {
+        let total = self.total.saturating_sub(last.total);
+        if total == 0 {
+            return 0.0;
+        }
+        self.busy.saturating_sub(last.busy) as f64 / total as f64 * 100.0
+    }
+}
+
+/// the total first, then one entry per core
+fn cpu_times() -> Vec<CpuTime> {
+    let stat = std::fs::read_to_string("/proc/stat").unwrap_or_default();
+    stat.lines()
+        .filter(|l| l.starts_with("cpu"))
+        .map(|l| {
+            // user nice system idle iowait irq softirq steal
+            let fields: Vec<u64> = l
+                .split_whitespace()
+                .skip(1)
+                .take(8)
+                .filter_map(|f| f.parse().ok())
+                .collect();
+            let total: u64 = fields.iter().sum();
+            let idle = fields.get(3).unwrap_or(&0) + fields.get(4).unwrap_or(&0);
+            CpuTime {
+                busy: total - idle,
+                total,
+            }
+        })
+        .collect()
+}
+
+#[derive(Default)]
+struct Memory {
+    total: u64,
+    available: u64,
+    swap_total: u64,
+    swap_free: u64,
+}
+
+impl Memory {
+    fn used(&self) -> u64 {
+        self.total.saturating_sub(self.available)
+    }
+    fn swap_used(&self) -> u64 {
+        self.swap_total.saturating_sub(self.swap_free)
+    }
+}
+
+fn memory_info() -> Memory {
+    let meminfo = std::fs::read_to_string("/proc/meminfo").unwrap_or_default();
+    let mut memory = Memory::default();
+    for line in meminfo.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        // values are in kB
+        let value = value
+            .trim()
+            .trim_end_matches(" kB")
+            .parse::<u64>()
+            .unwrap_or(0)
+            * 1024;
+        match key {
+            "MemTotal" => memory.total = value,
+            "MemAvailable" => memory.available = value,
+            "SwapTotal" => memory.swap_total = value,
+            "SwapFree" => memory.swap_free = value,
+            _ => {}
+        }
+    }
+    memory
+}
+
+fn percent(part: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f64 / total as f64 * 100.0
+    }
+}
+
+fn gib(bytes: u64) -> f64 {
+    bytes as f64 / 1024.0 / 1024.0 / 1024.0
+}
+
+fn cores() -> usize {
+    std::thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+/// sets the `warning`/`critical` classes depending on which limit `value` reached,
+/// returns that as the state for icon sets, `default` below both
+pub fn threshold(
+    widget: &impl IsA<Widget>,
+    value: f64,
+    warning: f64,
+    critical: f64,
+) -> &'static str {
+    let critical = value >= critical;
+    let warning = !critical && value >= warning;
+    for (class, on) in [("critical", critical), ("warning", warning)] {
+        if on {
+            widget.add_css_class(class);
+        } else {
+            widget.remove_css_class(class);
+        }
+    }
+    if critical {
+        "critical"
+    } else if warning {
+        "warning"
+    } else {
+        "default"
+    }
+}
+
+struct Module {
+    widget: Box,
+    icon: Icon,
+    label: Label,
+    graph: Sparkline,
+}
+
+/// the common layout, an icon, a label and the optional graph,
+/// `class` also picks the icon style
+fn module(class: &str, icon: (&str, &str), config: &Resource, max: Option<f64>) -> Module {
+    let widget = Box::new(Horizontal, 5);
+    widget.add_css_class("container");
+    widget.add_css_class(class);
+    let (glyph, name) = icon;
+    let icon = Icon::new(class);
+    icon.set(glyph, name);
+    let label = Label::new(None);
+    let graph = Sparkline::with_max(config.history, max);
+    widget.append(&icon.widget);
+    widget.append(&label);
+    if config.graph {
+        widget.append(&graph.widget);
+    }
+    Module {
+        widget,
+        icon,
+        label,
+        graph,
+    }
+}
+
+pub fn cpu() -> Box {
+    let config = &CONFIG.cpu;
+    let Module {
+        widget,
+        icon,
+        label,
+        graph,
+    } = module("cpu", ("󰻠", "cpu-symbolic"), config, Some(100.0));
+    let bars = Box::new(Horizontal, 1);
+    bars.add_css_class("cores");
+    if config.per_core {
+        widget.append(&bars);
+    }
+    spawn_future_local(clone! {
+        #[strong] widget,
+        async move {
+            let mut last = cpu_times();
+            let mut levels: Vec<LevelBar> = Vec::new();
+            loop {
+                sleep(interval(config.interval)).await;
+                let now = cpu_times();
+                let usage: Vec<f64> = now.iter().zip(&last).map(|(n, l)| n.usage(*l)).collect();
+                last = now;
+                let Some((total, cores)) = usage.split_first() else {
+                    continue;
+                };
+                label.set_text(
+                    &config
+                        .label
+                        .render("{percent}%", &[("percent", (*total).into())]),
+                );
+                graph.push(*total);
+                let state = threshold(&widget, *total, config.warning, config.critical);
+                if let Some(i) = config.label.icon(state, *total) {
+                    icon.set(i, i);
+                }
+                if config.per_core {
+                    // cpus can come and go with hotplug
+                    if levels.len() != cores.len() {
+                        for level in levels.drain(..) {
+                            bars.remove(&level);
+                        }
+                        for _ in cores {
+                            let level = LevelBar::for_interval(0.0, 100.0);
+                            level.set_orientation(Vertical);
+                            level.set_inverted(true);
+                            bars.append(&level);
+                            levels.push(level);
+                        }
+                    }
+                    for (level, core) in levels.iter().zip(cores) {
+                        level.set_value(*core);
+                    }
+                }
+                tooltip::set(
+                    &widget,
+                    &cores
+                        .iter()
+                        .enumerate()
+                        .map(|(i, c)| tooltip::row(&format!("core {i}"), &format!("{c:.0}%")))
+                        .collect::<Vec<String>>()
+                        .join("\n"),
+                );
+            }
+        }
+    });
+    widget
+}
+
+pub fn memory() -> Box {
+    let config = &CONFIG.memory;
+    let Module {
+        widget,
+        icon,
+        label,
+        graph,
+    } = module("memory", ("󰍛", "memory-symbolic"), config, Some(100.0));
+    spawn_future_local(clone! {
+        #[strong] widget,
+        async move {
+            loop {
+                let memory = memory_info();
+                let used = percent(memory.used(), memory.total);
+                label.set_text(&config.label.render(
+                    "{percent}%",
+                    &[
+                        ("percent", used.into()),
+                        ("used", Value::Number(gib(memory.used()), 1)),
+                        ("total", Value::Number(gib(memory.total), 1)),
+                        ("swap", Value::Number(gib(memory.swap_used()), 1)),
+                    ],
+                ));
+                graph.push(used);
+                let state = threshold(&widget, used, config.warning, config.critical);
+                if let Some(i) = config.label.icon(state, used) {
+                    icon.set(i, i);
+                }
+                let usage = |used, total| format!("{:.1} / {:.1} GiB", gib(used), gib(total));
+                let mut tooltip = tooltip::row("memory", &usage(memory.used(), memory.total));
+                if memory.swap_total > 0 {
+                    tooltip += "\n";
+                    tooltip += &tooltip::row("swap", &usage(memory.swap_used(), memory.swap_total));
+                }
+                tooltip::set(&widget, &tooltip);
+                sleep(interval(config.interval)).await;
+            }
+        }
+    });
+    widget
+}
+
+pub fn load() -> Box {
+    let config = &CONFIG.load;
+    let Module {
+        widget,
+        icon,
+        label,
+        graph,
+    } = module(
+        "load",
+        ("󰊚", "utilities-system-monitor-symbolic"),
+        config,
+        None,
+    );
+    spawn_future_local(clone! {
+        #[strong] widget,
+        async move {
+            loop {
+                // 0.52 0.58 0.59 1/467 12345
+                let loadavg = std::fs::read_to_string("/proc/loadavg").unwrap_or_default();
+                let averages: Vec<f64> = loadavg
+                    .split_whitespace()
+                    .take(3)
+                    .filter_map(|l| l.parse().ok())
+                    .collect();
+                if let [one, five, fifteen] = averages[..] {
+                    let per_core = one / cores() as f64 * 100.0;
+                    label.set_text(&config.label.render(
+                        "{load1}",
+                        &[
+                            ("load1", Value::Number(one, 2)),
+                            ("load5", Value::Number(five, 2)),
+                            ("load15", Value::Number(fifteen, 2)),
+                            ("percent", per_core.into()),
+                        ],
+                    ));
+                    graph.push(one);
+                    let state = threshold(&widget, per_core, config.warning, config.critical);
+                    if let Some(i) = config.label.icon(state, per_core) {
+                        icon.set(i, i);
+                    }
+                    let tooltip = [("1 min", one), ("5 min", five), ("15 min", fifteen)]
+                        .iter()
+                        .map(|(span, load)| tooltip::row(span, &format!("{load:.2}")))
+                        .collect::<Vec<String>>()
+                        .join("\n");
+                    tooltip::set(&widget, &tooltip);
+                }
+                sleep(interval(config.interval)).await;
+            }
+        }
+    });
+    widget
+}
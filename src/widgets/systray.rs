@@ -1,6 +1,7 @@
 use crate::*;
 use async_broadcast::Receiver;
 use hyprland::ctl;
+use libs::tooltip;
 use std::process::Command;
 
 pub fn new(mut listen_to: Receiver<bool>) -> Box {
@@ -8,6 +9,7 @@ pub fn new(mut listen_to: Receiver<bool>) -> Box {
     widget.add_css_class("container");
     widget.add_css_class("systray");
     widget.append(&Label::new(Some("^")));
+    tooltip::text(&widget, "show or hide the tray");
     let lclick = GestureClick::new();
     Command::new("eww")
         .args(["open", "temp-gtk-bar-systray"])
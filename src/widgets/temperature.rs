@@ -0,0 +1,176 @@
+use crate::*;
+use libs::icons::Icon;
+use libs::tooltip;
+use std::fs::{read_dir, read_to_string};
+use std::path::Path;
+use widgets::sysmon::{interval, threshold};
+
+struct Sensor {
+    chip: String,
+    label: String,
+    /// degrees celsius
+    temp: f64,
+    crit: Option<f64>,
+}
+
+impl Sensor {
+    fn matches(&self, name: &str) -> bool {
+        name == self.chip || name == self.label || name == format!("{}/{}", self.chip, self.label)
+    }
+}
+
+struct Fan {
+    chip: String,
+    label: String,
+    rpm: u64,
+}
+
+fn read(path: &Path) -> Option<String> {
+    read_to_string(path).ok().map(|s| s.trim().to_string())
+}
+
+/// sysfs temperatures are in millidegrees
+fn millidegrees(path: &Path) -> Option<f64> {
+    Some(read(path)?.parse::<f64>().ok()? / 1000.0)
+}
+
+fn hwmon(sensors: &mut Vec<Sensor>, fans: &mut Vec<Fan>) {
+    let Ok(chips) = read_dir("/sys/class/hwmon") else {
+        return;
+    };
+    for chip in chips.flatten() {
+        let dir = chip.path();
+        let name = read(&dir.join("name"))
+            .unwrap_or_else(|| chip.file_name().to_string_lossy().to_string());
+        let Ok(files) = read_dir(&dir) else {
+            continue;
+        };
+        let mut files: Vec<String> = files
+            .flatten()
+            .map(|f| f.file_name().to_string_lossy().to_string())
+            .collect();
+        files.sort();
+        for file in files {
+            // temp1_input, temp1_label, temp1_crit, fan1_input, fan1_label
+            let Some(prefix) = file.strip_suffix("_input") else {
+                continue;
+            };
+            let label = read(&dir.join(format!("{prefix}_label"))).unwrap_or(prefix.to_string());
+            if prefix.starts_with("temp") {
+                if let Some(temp) = millidegrees(&dir.join(&file)) {
+                    sensors.push(Sensor {
+                        chip: name.clone(),
+                        label,
+                        temp,
+                        crit: millidegrees(&dir.join(format!("{prefix}_crit"))),
+                    });
+                }
+            } else if prefix.starts_with("fan") {
+                if let Some(rpm) = read(&dir.join(&file)).and_then(|r| r.parse().ok()) {
+                    fans.push(Fan {
+                        chip: name.clone(),
+                        label,
+                        rpm,
+                    });
+                }
+            }
+        }
+    }
+}
+
+fn thermal_zones(sensors: &mut Vec<Sensor>) {
+    let Ok(zones) = read_dir("/sys/class/thermal") else {
+        return;
+    };
+    let mut zones: Vec<_> = zones
+        .flatten()
+        .filter(|z| z.file_name().to_string_lossy().starts_with("thermal_zone"))
+        .map(|z| z.path())
+        .collect();
+    zones.sort();
+    for dir in zones {
+        let Some(temp) = millidegrees(&dir.join("temp")) else {
+            continue;
+        };
+        let zone = dir
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        // the critical trip point is the one with type "critical"
+        let crit = (0..16)
+            .map_while(|i| read(&dir.join(format!("trip_point_{i}_type"))).map(|t| (i, t)))
+            .find(|(_, kind)| kind == "critical")
+            .and_then(|(i, _)| millidegrees(&dir.join(format!("trip_point_{i}_temp"))));
+        sensors.push(Sensor {
+            chip: zone,
+            label: read(&dir.join("type")).unwrap_or_default(),
+            temp,
+            crit,
+        });
+    }
+}
+
+pub fn new() -> Box {
+    let config = &CONFIG.temperature;
+    let widget = Box::new(Horizontal, 5);
+    widget.add_css_class("container");
+    widget.add_css_class("temperature");
+    let icon = Icon::new("temperature");
+    icon.set("󰔏", "sensors-temperature-symbolic");
+    let label = Label::new(None);
+    widget.append(&icon.widget);
+    widget.append(&label);
+    spawn_future_local(clone! {
+        #[strong] widget,
+        async move {
+            loop {
+                let mut sensors = Vec::new();
+                let mut fans = Vec::new();
+                hwmon(&mut sensors, &mut fans);
+                thermal_zones(&mut sensors);
+                let hottest = sensors
+                    .iter()
+                    .filter(|s| {
+                        config.sensors.is_empty() || config.sensors.iter().any(|n| s.matches(n))
+                    })
+                    .max_by(|a, b| a.temp.total_cmp(&b.temp));
+                widget.set_visible(hottest.is_some());
+                if let Some(sensor) = hottest {
+                    let critical = sensor.crit.unwrap_or(config.critical);
+                    // how close it is to critical, for picking icons
+                    let percent = sensor.temp / critical * 100.0;
+                    label.set_text(&config.label.render(
+                        "{temp}°C",
+                        &[
+                            ("temp", sensor.temp.into()),
+                            ("chip", sensor.chip.as_str().into()),
+                            ("sensor", sensor.label.as_str().into()),
+                            ("percent", percent.into()),
+                        ],
+                    ));
+                    let state =
+                        threshold(&widget, sensor.temp, config.warning.min(critical), critical);
+                    if let Some(i) = config.label.icon(state, percent) {
+                        icon.set(i, i);
+                    }
+                }
+                let tooltip = sensors
+                    .iter()
+                    .map(|s| {
+                        let name = format!("{}/{}", s.chip, s.label);
+                        tooltip::row(&name, &format!("{:.0}°C", s.temp))
+                    })
+                    .chain(fans.iter().map(|f| {
+                        let name = format!("{}/{}", f.chip, f.label);
+                        tooltip::row(&name, &format!("{} RPM", f.rpm))
+                    }))
+                    .collect::<Vec<String>>()
+                    .join("\n");
+                tooltip::set(&widget, &tooltip);
+                sleep(interval(config.interval)).await;
+            }
+        }
+    });
+    widget
+}
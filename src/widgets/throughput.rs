@@ -0,0 +1,203 @@
+use crate::*;
+use libs::config::RateUnit;
+use libs::icons::Icon;
+use libs::shared_widget::Sparkline;
+use libs::{process, tooltip};
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Instant;
+use widgets::network::default_interface;
+use widgets::sysmon;
+
+/// addresses rarely change, so `ip` isn't asked on every tick
+const ADDRESS_REFRESH: Duration = Duration::from_secs(10);
+
+#[derive(Deserialize)]
+struct Interface {
+    addr_info: Vec<Address>,
+}
+
+#[derive(Deserialize)]
+struct Address {
+    local: String,
+    prefixlen: u8,
+}
+
+fn read_counter(iface: &str, counter: &str) -> Option<u64> {
+    std::fs::read_to_string(format!("/sys/class/net/{iface}/statistics/{counter}"))
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+async fn addresses(iface: &str) -> Vec<String> {
+    let Ok(output) = process::output(&["ip", "-j", "addr", "show", "dev", iface]).await else {
+        return Vec::new();
+    };
+    from_str::<Vec<Interface>>(&output)
+        .unwrap_or_default()
+        .into_iter()
+        .flat_map(|i| i.addr_info)
+        .map(|a| format!("{}/{}", a.local, a.prefixlen))
+        .collect()
+}
+
+/// bytes per second between two readings of a counter, a counter that went back
+/// (the interface was reset) counts as no traffic
+fn rate(then: u64, now: u64, elapsed: Duration) -> f64 {
+    let secs = elapsed.as_secs_f64();
+    if secs > 0.0 {
+        now.saturating_sub(then) as f64 / secs
+    } else {
+        0.0
+    }
+}
+
+/// the label for one direction, `direction` is `down` or `up`
+fn label(bytes: f64, direction: &str) -> String {
+    CONFIG.throughput.label.render(
+        "{rate}",
+        &[
+            ("rate", format_rate(bytes, CONFIG.throughput.unit).into()),
+            ("bytes", bytes.into()),
+            ("direction", direction.into()),
+        ],
+    )
+}
+
+/// human readable rate for `bytes` per second
+pub fn format_rate(bytes: f64, unit: RateUnit) -> String {
+    let (mut value, suffix) = match unit {
+        RateUnit::Bytes => (bytes, "B/s"),
+        RateUnit::Bits => (bytes * 8.0, "b/s"),
+    };
+    let mut prefix = "";
+    for p in ["k", "M", "G", "T"] {
+        if value < 1000.0 {
+            break;
+        }
+        value /= 1000.0;
+        prefix = p;
+    }
+    if prefix.is_empty() {
+        format!("{value:.0} {suffix}")
+    } else {
+        format!("{value:.1} {prefix}{suffix}")
+    }
+}
+
+pub fn new() -> Box {
+    let config = &CONFIG.throughput;
+    let widget = Box::new(Horizontal, 5);
+    widget.add_css_class("container");
+    widget.add_css_class("throughput");
+    let down = Label::new(None);
+    let up = Label::new(None);
+    let down_graph = Sparkline::new(config.history);
+    let up_graph = Sparkline::new(config.history);
+    down_graph.widget.add_css_class("down");
+    up_graph.widget.add_css_class("up");
+    let down_icon = Icon::new("throughput");
+    let up_icon = Icon::new("throughput");
+    down_icon.set("󰇚", "network-receive-symbolic");
+    up_icon.set("󰕒", "network-transmit-symbolic");
+    widget.append(&down_icon.widget);
+    widget.append(&down);
+    if config.graph {
+        widget.append(&down_graph.widget);
+    }
+    widget.append(&up_icon.widget);
+    widget.append(&up);
+    if config.graph {
+        widget.append(&up_graph.widget);
+    }
+    // addresses are refreshed on the update tick, the tooltip only shows what's known
+    let known: Rc<RefCell<Option<(String, Vec<String>)>>> = Rc::new(RefCell::new(None));
+    tooltip::lazy(
+        &widget,
+        clone! {
+            #[strong] known,
+            move || {
+                let (iface, addresses) = known.borrow().clone()?;
+                Some(format!(
+                    "<b>{}</b>\n{}",
+                    tooltip::escape(&iface),
+                    tooltip::escape(&addresses.join("\n"))
+                ))
+            }
+        },
+    );
+    spawn_future_local(clone! {
+        #[strong] widget,
+        async move {
+            let interval = sysmon::interval(config.interval);
+            let mut last: Option<(String, u64, u64, Instant)> = None;
+            let mut looked_up: Option<Instant> = None;
+            loop {
+                let iface = config.interface.clone().or_else(default_interface);
+                if let Some(iface) = iface {
+                    // a new interface is looked up right away, the same one now and then
+                    let fresh = looked_up.is_some_and(|t| t.elapsed() < ADDRESS_REFRESH);
+                    let same = known.borrow().as_ref().is_some_and(|(known, _)| *known == iface);
+                    if !fresh || !same {
+                        let found = Some((iface.clone(), addresses(&iface).await));
+                        looked_up = Some(Instant::now());
+                        if *known.borrow() != found {
+                            known.replace(found);
+                            widget.trigger_tooltip_query();
+                        }
+                    }
+                    let rx = read_counter(&iface, "rx_bytes").unwrap_or(0);
+                    let tx = read_counter(&iface, "tx_bytes").unwrap_or(0);
+                    let now = Instant::now();
+                    // counters of a different interface aren't comparable, and the main loop
+                    // can run late so the time that really passed is used
+                    let previous = last.as_ref().filter(|l| l.0 == iface);
+                    if let Some((_, last_rx, last_tx, then)) = previous {
+                        let elapsed = now.duration_since(*then);
+                        let rx_rate = rate(*last_rx, rx, elapsed);
+                        let tx_rate = rate(*last_tx, tx, elapsed);
+                        down.set_text(&label(rx_rate, "down"));
+                        up.set_text(&label(tx_rate, "up"));
+                        down_graph.push(rx_rate);
+                        up_graph.push(tx_rate);
+                    }
+                    last = Some((iface, rx, tx, now));
+                } else {
+                    last = None;
+                    known.replace(None);
+                    down.set_text("-");
+                    up.set_text("-");
+                }
+                sleep(interval).await;
+            }
+        }
+    });
+    widget
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rate_over_elapsed_time() {
+        assert_eq!(rate(1000, 3000, Duration::from_secs(2)), 1000.0);
+        assert_eq!(rate(0, 500, Duration::from_millis(250)), 2000.0);
+    }
+
+    #[test]
+    fn rate_without_time_or_after_reset() {
+        assert_eq!(rate(1000, 3000, Duration::ZERO), 0.0);
+        assert_eq!(rate(3000, 1000, Duration::from_secs(1)), 0.0);
+    }
+
+    #[test]
+    fn rate_formatting() {
+        assert_eq!(format_rate(512.0, RateUnit::Bytes), "512 B/s");
+        assert_eq!(format_rate(1500.0, RateUnit::Bytes), "1.5 kB/s");
+        assert_eq!(format_rate(1500.0, RateUnit::Bits), "12.0 kb/s");
+        assert_eq!(format_rate(2_500_000_000.0, RateUnit::Bytes), "2.5 GB/s");
+    }
+}
@@ -3,11 +3,14 @@ use anyhow::Result;
 use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 
+use libs::animation::{self, Transition};
+use libs::icons::Icon;
 use libs::pulse::{change_channel_volume_by_percent, channel_volume_by_percent, Pulse};
+use libs::tooltip;
 use pulseaudio::protocol::command::{
     SinkInfo, SubscriptionEvent, SubscriptionEventFacility, SubscriptionEventType,
 };
-use windows::popup_volume::PopUpVolume;
+use windows::popup_osd::PopUpOsd;
 
 pub fn get_volume(info: &SinkInfo) -> Result<f32> {
     let base_vol = info.base_volume.as_u32() as f32;
@@ -31,25 +34,59 @@ pub fn get_icon(mute: bool, volume: f32) -> String {
     })
 }
 
+/// the theme icon matching `get_icon`
+fn icon_name(mute: bool, volume: f32) -> &'static str {
+    if mute {
+        "audio-volume-muted-symbolic"
+    } else if volume > 66.0 {
+        "audio-volume-high-symbolic"
+    } else if volume > 33.0 {
+        "audio-volume-medium-symbolic"
+    } else {
+        "audio-volume-low-symbolic"
+    }
+}
+
 pub fn new(app: &Application) -> Box {
     // TODO :: maybe reducing connection to pulseaudio if nessesary
     let pulse_event = libs::pulse::Pulse::new("z3phyrl.gtk-bar.event").unwrap();
     let pulse_ctl = libs::pulse::Pulse::new("z3phyrl.gtk-bar.ctl").unwrap();
     let pulse_info = libs::pulse::Pulse::new("z3phyrl.gtk-bar.info").unwrap();
     let subscription = pulse_event.subscribe().unwrap();
-    let popup_volume = PopUpVolume::new(app);
+    let popup_volume = {
+        let pulse_info = Rc::new(Pulse::new("z3phyrl.popup-volume.info").unwrap());
+        let pulse_ctl = Pulse::new("z3phyrl.popup-volume.ctl").unwrap();
+        PopUpOsd::new(
+            app,
+            "volume",
+            "volume",
+            clone! {
+                #[strong] pulse_info,
+                move |v| {
+                    if let Ok(info) = pulse_info.get_sink_info(0) {
+                        let channels = info.cvolume.channels().len() as u32;
+                        let volume = v * 0.01;
+                        let volume = channel_volume_by_percent(info.base_volume, channels, volume);
+                        let _ = pulse_ctl.set_sink_volume(0, volume);
+                    }
+                }
+            },
+            move |v| {
+                let muted = pulse_info.get_sink_info(0).is_ok_and(|i| i.muted);
+                (get_icon(muted, v as f32), icon_name(muted, v as f32))
+            },
+        )
+    };
 
     let widget = Box::new(Horizontal, 10);
     widget.add_css_class("container");
     widget.add_css_class("volume");
-    let icon = Label::new(None);
+    let icon = Icon::new("volume");
     let scale = Scale::with_range(Horizontal, 0.0, 100.0, 1.0);
-    let revealer = Revealer::builder()
-        .transition_type(SlideRight)
-        .child(&scale)
-        .build();
+    let revealer = animation::revealer(Transition::Slide, CONFIG.animation.durations.volume_slider);
+    revealer.set_child(Some(&scale));
     let label = Label::new(None);
-    widget.append(&icon);
+    widget.append(&icon.widget);
     widget.append(&revealer);
     widget.append(&label);
 
@@ -177,8 +214,24 @@ pub fn new(app: &Application) -> Box {
     widget.add_controller(expand);
     widget.add_controller(motion);
     widget.add_controller(scroll);
-    icon.add_controller(inhibit);
-    icon.add_controller(mute);
+    icon.widget.add_controller(inhibit);
+    icon.widget.add_controller(mute);
+    tooltip::lazy(
+        &widget,
+        clone! {
+            #[strong] pulse_info,
+            move || {
+                let info = pulse_info.get_sink_info(0).ok()?;
+                let sink = info.description.as_ref().unwrap_or(&info.name).to_string_lossy();
+                let volume = get_volume(&info).ok()?;
+                Some(format!(
+                    "<b>{}</b>\n{volume:.0}%{}",
+                    tooltip::escape(&sink),
+                    if info.muted { ", muted" } else { "" }
+                ))
+            }
+        },
+    );
 
     // set up event subcription
 
@@ -186,18 +239,34 @@ pub fn new(app: &Application) -> Box {
         let update = move || {
             if let Ok(info) = pulse_info.get_sink_info(0) {
                 if let Ok(volume) = get_volume(&info) {
-                    icon.set_text(&get_icon(info.muted, volume));
+                    let state = if info.muted { "muted" } else { "default" };
+                    // configured icons are glyphs or icon names, whichever the style uses
+                    let icon_text = match CONFIG.volume.icon(state, volume as f64) {
+                        Some(configured) => {
+                            icon.set(configured, configured);
+                            configured.to_string()
+                        }
+                        None => {
+                            let glyph = get_icon(info.muted, volume);
+                            icon.set(&glyph, icon_name(info.muted, volume));
+                            glyph
+                        }
+                    };
                     // TODO :: maybe make the scale interpolate between values
                     scale.set_value(volume as f64);
-                    label.set_text(&format!("{volume:.0}%"));
+                    label.set_text(&CONFIG.volume.render(
+                        "{volume}%",
+                        &[
+                            ("volume", (volume as f64).into()),
+                            ("icon", icon_text.into()),
+                            ("muted", if info.muted { "muted" } else { "" }.into()),
+                        ],
+                    ));
                     // if !hover.get() {
                     //     popup_volume.present(true);
                     // }
                     // if popup_volume.presenting() {
-                    //     popup_volume.update(
-                    //         &format!("{} {volume:.0}%", &get_icon(info.muted, volume)),
-                    //         volume as f64,
-                    //     );
+                    //     popup_volume.update(volume as f64);
                     // }
                 }
             }
@@ -211,6 +280,7 @@ pub fn new(app: &Application) -> Box {
                     index: _,
                 }) => {
                     update();
+                    libs::autohide::request_attention();
                     // println!("SINK => {index:?}");
                 }
                 Ok(SubscriptionEvent {
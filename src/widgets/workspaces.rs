@@ -1,206 +1,538 @@
 use crate::*;
 use async_broadcast::Receiver;
+use gtk::{gdk::Key, Entry, EventControllerKey, Popover};
 use hyprland::ctl;
+use libs::animation::{self, Sequence, Transition};
+use libs::shared_widget::orientation;
+use libs::tooltip;
+use std::collections::{BTreeMap, HashSet};
 
-#[derive(Deserialize, Debug, Hash, Eq, PartialEq, Clone)]
+#[derive(Deserialize)]
+struct MonitorInfo {
+    name: String,
+    #[serde(rename = "activeWorkspace")]
+    active_workspace: ActiveWorkspace,
+    #[serde(rename = "specialWorkspace")]
+    special_workspace: ActiveWorkspace,
+    focused: bool,
+}
+
+#[derive(Deserialize)]
+struct ActiveWorkspace {
+    id: i32,
+    name: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
 struct WorkspaceInfo {
     id: i32,
     name: String,
+    #[serde(default)]
+    monitor: String,
+}
+
+impl WorkspaceInfo {
+    /// the name `togglespecialworkspace` expects, `special:term` becomes `term`
+    fn special_name(&self) -> &str {
+        self.name.strip_prefix("special:").unwrap_or(&self.name)
+    }
 }
 
 #[derive(Debug, Clone)]
 struct Workspace {
+    info: WorkspaceInfo,
     special: bool,
     widget: Box,
-    slidein: Revealer,
-    crossfade: Revealer,
+    /// slides in to make room, then fades in
+    reveal: Sequence,
     expander: Revealer,
+    main: Box,
+    label: Label,
+    menu: Popover,
 }
 
 impl Workspace {
     fn new(info: &WorkspaceInfo, special: bool) -> Self {
-        let widget = Box::new(Horizontal, 0);
-        let slidein = Revealer::builder()
-            .transition_type(SlideLeft)
-            .transition_duration(250)
-            .build();
-        let crossfade = Revealer::builder()
-            .transition_type(Crossfade)
-            .transition_duration(500)
-            .build();
-        let main = Box::new(Horizontal, 0);
+        let widget = Box::new(orientation(), 0);
+        let main = Box::new(orientation(), 0);
         let anchor = Box::default();
-        let expander = Revealer::builder()
-            .transition_type(SlideLeft)
-            .transition_duration(1000)
-            .build();
+        let durations = &CONFIG.animation.durations;
+        let expander = animation::revealer(Transition::Expand, durations.workspace_expand);
         let expand = Box::default();
         if special {
             main.add_css_class("specialworkspace")
         } else {
             main.add_css_class("workspace");
         }
+        let label = Label::new(None);
         anchor.add_css_class("anchor");
         expand.add_css_class("expand");
-        widget.append(&slidein);
-        slidein.set_child(Some(&crossfade));
-        crossfade.set_child(Some(&main));
+        anchor.append(&label);
+        let stages = [
+            (Transition::Expand, durations.workspace_slide),
+            (Transition::Fade, durations.workspace_fade),
+        ];
+        let reveal = Sequence::new(&main, &stages);
+        widget.append(&reveal.widget);
         main.append(&anchor);
         main.append(&expander);
         expander.set_child(Some(&expand));
         let lclick = GestureClick::new();
+        let mclick = GestureClick::builder().button(2).build();
+        let rclick = GestureClick::builder().button(3).build();
         let id = info.id;
+        let menu = menu(id);
+        menu.set_parent(&widget);
+        // special workspaces can't be switched to by id, only toggled by name
+        let (switch, target) = if special {
+            let name = match info.special_name() {
+                "special" => "",
+                name => name,
+            };
+            (
+                format!("dispatch togglespecialworkspace {name}"),
+                info.name.clone(),
+            )
+        } else {
+            (format!("dispatch workspace {id}"), id.to_string())
+        };
         lclick.connect_pressed(move |click, count, x, y| {
-            ctl(&format!("dispatch workspace {}", id));
+            ctl(&switch);
+        });
+        mclick.connect_pressed(move |_, _, _, _| {
+            ctl(&format!("dispatch movetoworkspacesilent {target}"));
+        });
+        rclick.connect_pressed(clone! {
+            #[strong] menu,
+            move |_, _, _, _| {
+                menu.popup();
+            }
         });
         widget.add_controller(lclick);
-        Self {
+        widget.add_controller(mclick);
+        widget.add_controller(rclick);
+        let this = Self {
+            info: info.clone(),
             special,
             widget,
-            slidein,
-            crossfade,
+            reveal,
             expander,
-        }
+            main,
+            label,
+            menu,
+        };
+        this.relabel();
+        this
     }
-    async fn reveal(&self, reveal: bool) {
-        if reveal {
-            self.slidein.set_reveal_child(reveal);
-            sleep(Duration::from_millis(100)).await;
-            self.crossfade.set_reveal_child(reveal);
+    fn expand(&self, expand: bool) {
+        self.expander.set_reveal_child(expand);
+    }
+    fn relabel(&self) {
+        if let Some(label) = label(&self.info) {
+            self.main.add_css_class("labeled");
+            self.label.set_text(&label);
+            self.label.set_visible(true);
         } else {
-            self.crossfade.set_reveal_child(reveal);
-            sleep(Duration::from_millis(250)).await;
-            self.slidein.set_reveal_child(reveal);
+            self.main.remove_css_class("labeled");
+            self.label.set_visible(false);
+        }
+        let mut lines = vec![tooltip::row("workspace", self.info.special_name())];
+        if !self.info.monitor.is_empty() {
+            lines.push(tooltip::row("monitor", &self.info.monitor));
         }
+        tooltip::set(&self.widget, &lines.join("\n"));
     }
-    fn revealed(&self) -> bool {
-        self.slidein.reveals_child() || self.crossfade.reveals_child()
+    fn empty(&self, empty: bool) {
+        if empty {
+            self.main.add_css_class("empty");
+        } else {
+            self.main.remove_css_class("empty");
+        }
     }
-    fn expand(&self, expand: bool) {
-        self.expander.set_reveal_child(expand);
+}
+
+fn persistent(id: i32) -> bool {
+    CONFIG.workspaces.persistent.contains(&id)
+}
+
+fn label(info: &WorkspaceInfo) -> Option<String> {
+    let labels = &CONFIG.workspaces.labels;
+    let label = labels
+        .get(&info.id.to_string())
+        .or_else(|| labels.get(&info.name))
+        .cloned();
+    if info.id < 0 {
+        // scratchpads are told apart by name so they always get one
+        label
+            .or_else(|| labels.get(info.special_name()).cloned())
+            .or_else(|| Some(info.special_name().to_string()))
+    } else {
+        label.or_else(|| {
+            (CONFIG.workspaces.show_names && info.name != info.id.to_string())
+                .then(|| info.name.clone())
+        })
     }
 }
 
-pub fn new(mut event_listener: Receiver<String>) -> Box {
-    let widget = Box::new(Horizontal, 0);
-    let workspaces_widget = widget.clone();
-    let mut workspaces: HashMap<WorkspaceInfo, Workspace> = HashMap::new();
-    spawn_future_local(async move {
-        for info in from_str::<Vec<WorkspaceInfo>>(&hyprland::ctl("j/workspaces")).unwrap() {
-            let workspace = Workspace::new(&info, false);
-            let before: Option<(&WorkspaceInfo, &Workspace)> = workspaces
-                .iter()
-                .filter(|w| w.0.id < info.id)
-                .max_by_key(|(i, _)| i.id);
-            if info.id < 0 {
-                let workspace = Workspace::new(&info, true);
-                workspaces_widget.insert_child_after(&workspace.widget, None::<&Box>);
-                workspace.reveal(true).await;
-                workspaces.insert(info, workspace.clone());
-                continue;
-            } else if let Some(before) = before {
-                workspaces_widget.insert_child_after(&workspace.widget, Some(&before.1.widget));
-            } else if info.id == 1 {
-                workspaces_widget.insert_child_after(&workspace.widget, None::<&Box>);
+fn menu(id: i32) -> Popover {
+    let menu = Popover::builder().has_arrow(false).build();
+    let content = Box::new(Vertical, 5);
+    let rename = Entry::builder().placeholder_text("Rename").build();
+    let monitors = Box::new(Vertical, 0);
+    content.add_css_class("workspace-menu");
+    rename.connect_activate(clone! {
+        #[strong] menu,
+        move |entry| {
+            ctl(&format!("dispatch renameworkspace {id} {}", entry.text()));
+            entry.set_text("");
+            menu.popdown();
+        }
+    });
+    // monitors can come and go so the list is rebuilt every time the menu is opened
+    menu.connect_show(clone! {
+        #[strong] monitors,
+        #[weak] menu,
+        move |_| {
+            while let Some(child) = monitors.first_child() {
+                monitors.remove(&child);
+            }
+            for monitor in from_str::<Vec<MonitorInfo>>(&ctl("j/monitors")).unwrap_or_default() {
+                let button = Button::with_label(&format!("Move to {}", monitor.name));
+                button.connect_clicked(clone! {
+                    #[weak] menu,
+                    move |_| {
+                        ctl(&format!("dispatch moveworkspacetomonitor {id} {}", monitor.name));
+                        menu.popdown();
+                    }
+                });
+                monitors.append(&button);
+            }
+        }
+    });
+    content.append(&rename);
+    content.append(&monitors);
+    menu.set_child(Some(&content));
+    menu
+}
+
+/// the `workspace` dispatcher argument for moving `offset` workspaces away from the current one
+fn relative(offset: i32) -> String {
+    let prefix = match (
+        CONFIG.workspaces.scroll_occupied_only,
+        CONFIG.workspaces.scroll_per_monitor,
+    ) {
+        (true, true) => "m",
+        (true, false) => "e",
+        (false, true) => "r",
+        (false, false) => "",
+    };
+    format!("{prefix}{offset:+}")
+}
+
+struct Workspaces {
+    widget: Box,
+    workspaces: BTreeMap<i32, Workspace>,
+    /// monitor names in the order hyprland reports them
+    monitors: Vec<String>,
+    /// the special workspace open on each monitor
+    specials: HashMap<String, String>,
+}
+
+impl Workspaces {
+    fn new(widget: Box) -> Self {
+        Self {
+            widget,
+            workspaces: BTreeMap::new(),
+            monitors: Vec::new(),
+            specials: HashMap::new(),
+        }
+    }
+    /// bring everything in line with what hyprland currently has
+    async fn sync(&mut self) {
+        let monitors = from_str::<Vec<MonitorInfo>>(&ctl("j/monitors")).unwrap_or_default();
+        let infos = from_str::<Vec<WorkspaceInfo>>(&ctl("j/workspaces")).unwrap_or_default();
+        self.monitors = monitors.iter().map(|m| m.name.clone()).collect();
+        self.specials = monitors
+            .iter()
+            .filter(|m| !m.special_workspace.name.is_empty())
+            .map(|m| (m.name.clone(), m.special_workspace.name.clone()))
+            .collect();
+        let gone: Vec<i32> = self
+            .workspaces
+            .keys()
+            .filter(|id| !infos.iter().any(|i| i.id == **id))
+            .copied()
+            .collect();
+        for id in gone {
+            self.destroy(id);
+        }
+        for info in infos {
+            if let Some(workspace) = self.workspaces.get_mut(&info.id) {
+                workspace.info = info;
+                workspace.relabel();
+                workspace.empty(false);
             } else {
-                workspaces_widget.append(&workspace.widget);
+                self.create(info).await;
+            }
+        }
+        for &id in &CONFIG.workspaces.persistent {
+            if !self.workspaces.contains_key(&id) {
+                let info = WorkspaceInfo {
+                    id,
+                    name: id.to_string(),
+                    monitor: String::new(),
+                };
+                self.create(info).await;
+                if let Some(workspace) = self.workspaces.get(&id) {
+                    workspace.empty(true);
+                }
+            }
+        }
+        self.sort();
+        self.expand_special();
+        if let Some(monitor) = monitors.iter().find(|m| m.focused) {
+            self.focus(monitor.active_workspace.id);
+        }
+    }
+    async fn create(&mut self, info: WorkspaceInfo) {
+        if let Some(workspace) = self.workspaces.get_mut(&info.id) {
+            // persistent workspaces are never removed, they just stop being empty
+            workspace.info = info;
+            workspace.relabel();
+            workspace.empty(false);
+            return;
+        }
+        let workspace = Workspace::new(&info, info.id < 0);
+        self.widget.append(&workspace.widget);
+        self.workspaces.insert(info.id, workspace.clone());
+        self.sort();
+        self.expand_special();
+        workspace.reveal.animate(true).await;
+    }
+    fn destroy(&mut self, id: i32) {
+        if persistent(id) {
+            if let Some(workspace) = self.workspaces.get(&id) {
+                workspace.empty(true);
             }
+            return;
+        }
+        if let Some(workspace) = self.workspaces.remove(&id) {
+            spawn_future_local(clone! {
+                #[strong(rename_to = workspaces_widget)] self.widget,
+                async move {
+                    if workspace.reveal.revealed() {
+                        workspace.reveal.animate(false).await;
+                        // the slide is the last stage to start, wait for it before removing
+                        animation::pause(CONFIG.animation.durations.workspace_slide).await;
+                    }
+                    workspace.menu.unparent();
+                    workspaces_widget.remove(&workspace.widget);
+                }
+            });
+        }
+    }
+    /// the id of the workspace called `name`, for events that only carry names
+    fn id(&self, name: &str) -> Option<i32> {
+        self.workspaces
+            .values()
+            .find(|w| w.info.name == name)
+            .map(|w| w.info.id)
+    }
+    fn rename(&mut self, id: i32, name: &str) {
+        if let Some(workspace) = self.workspaces.get_mut(&id) {
+            workspace.info.name = name.to_string();
+            workspace.relabel();
+        }
+    }
+    fn moved(&mut self, id: i32, monitor: &str) {
+        if let Some(workspace) = self.workspaces.get_mut(&id) {
+            workspace.info.monitor = monitor.to_string();
+            workspace.relabel();
+        }
+        self.sort();
+    }
+    fn focus(&self, id: i32) {
+        for workspace in self.workspaces.values() {
+            if !workspace.special {
+                workspace.expand(workspace.info.id == id);
+            }
+        }
+    }
+    fn special(&mut self, name: &str, monitor: &str) {
+        if name.is_empty() {
+            self.specials.remove(monitor);
+        } else {
+            self.specials.insert(monitor.to_string(), name.to_string());
+        }
+        self.expand_special();
+    }
+    fn expand_special(&self) {
+        for workspace in self.workspaces.values() {
+            if workspace.special {
+                workspace.expand(self.specials.values().any(|n| *n == workspace.info.name));
+            }
+        }
+    }
+    /// special workspaces first, then by id, optionally grouped by monitor
+    fn sort(&self) {
+        let mut order: Vec<&Workspace> = self.workspaces.values().collect();
+        order.sort_by_key(|w| {
+            let monitor = if CONFIG.workspaces.group_by_monitor {
+                self.monitors
+                    .iter()
+                    .position(|m| *m == w.info.monitor)
+                    .unwrap_or(usize::MAX)
+            } else {
+                0
+            };
+            (!w.special, monitor, w.info.id)
+        });
+        let mut before: Option<&Box> = None;
+        for workspace in order {
+            self.widget.reorder_child_after(&workspace.widget, before);
+            before = Some(&workspace.widget);
+        }
+    }
+}
 
-            workspace.reveal(true).await;
-            workspaces.insert(info, workspace.clone());
+pub fn new(mut event_listener: Receiver<String>) -> Box {
+    let widget = Box::new(orientation(), 0);
+    let scroll = EventControllerScroll::new(
+        EventControllerScrollFlags::VERTICAL | EventControllerScrollFlags::DISCRETE,
+    );
+    let keys = EventControllerKey::new();
+    scroll.connect_scroll(|_, _, dy| {
+        if dy < 0.0 {
+            ctl(&format!("dispatch workspace {}", relative(-1)));
+        } else if dy > 0.0 {
+            ctl(&format!("dispatch workspace {}", relative(1)));
+        }
+        Propagation::Stop
+    });
+    keys.connect_key_pressed(|_, key, _, _| match key {
+        Key::Left | Key::Up | Key::h | Key::k => {
+            ctl(&format!("dispatch workspace {}", relative(-1)));
+            Propagation::Stop
         }
-        if let Some(current) = workspaces.get(&from_str(&ctl("j/activeworkspace")).unwrap()) {
-            current.expand(true);
+        Key::Right | Key::Down | Key::l | Key::j => {
+            ctl(&format!("dispatch workspace {}", relative(1)));
+            Propagation::Stop
         }
+        _ => Propagation::Proceed,
+    });
+    widget.set_focusable(true);
+    widget.add_controller(scroll);
+    widget.add_controller(keys);
+    let mut workspaces = Workspaces::new(widget.clone());
+    spawn_future_local(async move {
+        workspaces.sync().await;
+        // older hyprland only sends the events without ids, newer ones send both kinds,
+        // the old kind is handled until its v2 counterpart has shown up
+        let mut seen_v2: HashSet<String> = HashSet::new();
         loop {
             if let Ok(event) = event_listener.recv().await {
-                let mut event = event.split(">>");
-                match event.next() {
-                    Some("workspacev2") => {
-                        if let Some(data) = event.next() {
-                            let data: Vec<&str> = data.split(",").collect();
-                            // println!("{data:?}");
-                            let current = WorkspaceInfo {
-                                id: data[0].parse::<i32>().unwrap(),
-                                name: data[1].to_string(),
-                            };
-                            for (info, workspace) in &workspaces {
-                                if !workspace.special {
-                                    workspace.expand(*info == current);
-                                }
-                            }
+                let Some((event, data)) = event.split_once(">>") else {
+                    continue;
+                };
+                if let Some(old) = event.strip_suffix("v2") {
+                    seen_v2.insert(old.to_string());
+                }
+                let legacy = !seen_v2.contains(event);
+                match event {
+                    "workspacev2" => {
+                        if let Some(Ok(id)) = data.split(",").next().map(str::parse::<i32>) {
+                            workspaces.focus(id);
+                        }
+                    }
+                    "focusedmonv2" => {
+                        if let Some(Ok(id)) = data.rsplit(",").next().map(str::parse::<i32>) {
+                            workspaces.focus(id);
                         }
                     }
-                    Some("createworkspacev2") => {
-                        // println!("::createworkspacev2");
-                        if let Some(data) = event.next() {
-                            let data: Vec<&str> = data.split(",").collect();
-                            let info = WorkspaceInfo {
-                                id: data[0].parse::<i32>().unwrap(),
-                                name: data[1].to_string(),
-                            };
-                            let workspace = Workspace::new(&info, false);
-                            let before: Option<(&WorkspaceInfo, &Workspace)> = workspaces
-                                .iter()
-                                .filter(|w| w.0.id < info.id)
-                                .max_by_key(|(i, _)| i.id);
-                            if info.id < 0 {
-                                let workspace = Workspace::new(&info, true);
-                                workspaces_widget
-                                    .insert_child_after(&workspace.widget, None::<&Box>);
-                                workspace.reveal(true).await;
-                                workspaces.insert(info, workspace.clone());
-                                continue;
-                            } else if let Some(before) = before {
-                                workspaces_widget
-                                    .insert_child_after(&workspace.widget, Some(&before.1.widget));
-                            } else if info.id == 1 {
-                                workspaces_widget
-                                    .insert_child_after(&workspace.widget, None::<&Box>);
-                            } else {
-                                workspaces_widget.append(&workspace.widget);
+                    "createworkspacev2" => {
+                        if let Some((id, name)) = data.split_once(",") {
+                            if let Ok(id) = id.parse::<i32>() {
+                                let monitor = from_str::<Vec<WorkspaceInfo>>(&ctl("j/workspaces"))
+                                    .unwrap_or_default()
+                                    .into_iter()
+                                    .find(|i| i.id == id)
+                                    .map(|i| i.monitor)
+                                    .unwrap_or_default();
+                                let info = WorkspaceInfo {
+                                    id,
+                                    name: name.to_string(),
+                                    monitor,
+                                };
+                                workspaces.create(info).await;
                             }
-                            workspace.reveal(true).await;
-                            workspaces.insert(info, workspace.clone());
                         }
                     }
-                    Some("destroyworkspacev2") => {
-                        // println!("::destroyworkspacev2");
-                        if let Some(data) = event.next() {
-                            let data: Vec<&str> = data.split(",").collect();
-                            let info = WorkspaceInfo {
-                                id: data[0].parse::<i32>().unwrap(),
-                                name: data[1].to_string(),
-                            };
-                            let ws = workspaces.clone();
-                            let workspaces_widget = workspaces_widget.clone();
-                            workspaces.remove(&info);
-                            spawn_future_local(async move {
-                                if let Some(widget) = ws.get(&info) {
-                                    if widget.revealed() {
-                                        widget.reveal(false).await;
-                                        sleep(Duration::from_millis(150)).await;
-                                    }
-                                    workspaces_widget.remove(&widget.widget);
-                                }
-                            });
+                    "destroyworkspacev2" => {
+                        if let Some(Ok(id)) = data.split(",").next().map(str::parse::<i32>) {
+                            workspaces.destroy(id);
                         }
                     }
-                    Some("activespecial") => {
-                        if let Some(data) = event.next() {
-                            let data: Vec<&str> = data.split(",").collect();
-                            let workspace_name = data[0];
-                            let mut open = true;
-                            if workspace_name.is_empty() {
-                                open = false
+                    "renameworkspace" => {
+                        if let Some((id, name)) = data.split_once(",") {
+                            if let Ok(id) = id.parse::<i32>() {
+                                workspaces.rename(id, name);
                             }
-                            for (_, workspace) in &workspaces {
-                                if workspace.special {
-                                    workspace.expand(open);
-                                }
+                        }
+                    }
+                    "moveworkspacev2" => {
+                        // WORKSPACEID,WORKSPACENAME,MONNAME and the name may contain commas
+                        if let (Some((id, _)), Some((_, monitor))) =
+                            (data.split_once(","), data.rsplit_once(","))
+                        {
+                            if let Ok(id) = id.parse::<i32>() {
+                                workspaces.moved(id, monitor);
                             }
                         }
                     }
+                    "monitoraddedv2" | "monitorremovedv2" => {
+                        workspaces.sync().await;
+                    }
+                    "workspace" if legacy => {
+                        if let Some(id) = workspaces.id(data) {
+                            workspaces.focus(id);
+                        }
+                    }
+                    "focusedmon" if legacy => {
+                        // MONNAME,WORKSPACENAME
+                        if let Some(id) = data.split_once(",").and_then(|(_, n)| workspaces.id(n)) {
+                            workspaces.focus(id);
+                        }
+                    }
+                    "createworkspace" if legacy => {
+                        let info = from_str::<Vec<WorkspaceInfo>>(&ctl("j/workspaces"))
+                            .unwrap_or_default()
+                            .into_iter()
+                            .find(|i| i.name == data);
+                        if let Some(info) = info {
+                            workspaces.create(info).await;
+                        }
+                    }
+                    "destroyworkspace" if legacy => {
+                        if let Some(id) = workspaces.id(data) {
+                            workspaces.destroy(id);
+                        }
+                    }
+                    "moveworkspace" if legacy => {
+                        // WORKSPACENAME,MONNAME
+                        if let Some((name, monitor)) = data.rsplit_once(",") {
+                            if let Some(id) = workspaces.id(name) {
+                                workspaces.moved(id, monitor);
+                            }
+                        }
+                    }
+                    "monitoradded" | "monitorremoved" if legacy => {
+                        workspaces.sync().await;
+                    }
+                    "activespecial" => {
+                        if let Some((workspace_name, monitor)) = data.rsplit_once(",") {
+                            workspaces.special(workspace_name, monitor);
+                        }
+                    }
                     _e => {
                         // println!("{e:?}")
                     }
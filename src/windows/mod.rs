@@ -1 +1,2 @@
-pub mod popup_volume;
+pub mod popup_notifications;
+pub mod popup_osd;
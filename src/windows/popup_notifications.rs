@@ -0,0 +1,184 @@
+use crate::*;
+use gtk::{pango, Image};
+use libs::icons::Icon;
+use libs::notifications::{Daemon, Event, Notification, Reason, Urgency};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+const WIDTH: i32 = 360;
+
+fn icon(name: &str) -> Option<Image> {
+    if name.is_empty() {
+        return None;
+    }
+    let image = match name.strip_prefix("file://") {
+        Some(path) => Image::from_file(path),
+        None if name.starts_with('/') => Image::from_file(name),
+        None => Image::from_icon_name(name),
+    };
+    image.set_pixel_size(32);
+    Some(image)
+}
+
+/// a notification as it shows up in popups and the history,
+/// `history` decides whether closing it drops it or only takes the popup down
+pub fn card(daemon: &Daemon, notification: &Notification, history: bool) -> Box {
+    let id = notification.id;
+    let widget = Box::new(Vertical, 5);
+    widget.add_css_class("notification");
+    widget.add_css_class(match notification.urgency {
+        Urgency::Low => "low",
+        Urgency::Normal => "normal",
+        Urgency::Critical => "critical",
+    });
+    let header = Box::new(Horizontal, 10);
+    if let Some(image) = icon(&notification.icon) {
+        header.append(&image);
+    }
+    let titles = Box::new(Vertical, 0);
+    titles.set_hexpand(true);
+    let summary = Label::builder()
+        .label(&notification.summary)
+        .xalign(0.0)
+        .wrap(true)
+        .css_classes(["summary"])
+        .build();
+    let app = Label::builder()
+        .label(format!(
+            "{} · {}",
+            notification.app_name,
+            notification.time.format("%H:%M")
+        ))
+        .xalign(0.0)
+        .css_classes(["app-name"])
+        .build();
+    titles.append(&summary);
+    titles.append(&app);
+    header.append(&titles);
+    let close_icon = Icon::new("notifications");
+    close_icon.set("󰅖", "window-close-symbolic");
+    let close = Button::builder().child(&close_icon.widget).build();
+    close.add_css_class("close");
+    close.connect_clicked(clone! {
+        #[strong] daemon,
+        move |_| {
+            if history {
+                daemon.remove(id);
+            } else {
+                daemon.close(id, Reason::Dismissed);
+            }
+        }
+    });
+    header.append(&close);
+    widget.append(&header);
+    if !notification.body.is_empty() {
+        let body = Label::builder()
+            .xalign(0.0)
+            .wrap(true)
+            .css_classes(["body"])
+            .build();
+        // senders aren't always careful with markup
+        if pango::parse_markup(&notification.body, '\0').is_ok() {
+            body.set_markup(&notification.body);
+        } else {
+            body.set_text(&notification.body);
+        }
+        widget.append(&body);
+    }
+    let actions = Box::new(Horizontal, 5);
+    actions.add_css_class("actions");
+    for (key, label) in &notification.actions {
+        // the default action is what clicking the notification does
+        if key == "default" {
+            continue;
+        }
+        let button = Button::with_label(label);
+        button.set_hexpand(true);
+        button.connect_clicked(clone! {
+            #[strong] daemon,
+            #[strong] key,
+            move |_| daemon.invoke(id, &key)
+        });
+        actions.append(&button);
+    }
+    if actions.first_child().is_some() {
+        widget.append(&actions);
+    }
+    if notification.actions.iter().any(|(key, _)| key == "default") {
+        let click = GestureClick::new();
+        click.connect_released(clone! {
+            #[strong] daemon,
+            move |_, _, _, _| daemon.invoke(id, "default")
+        });
+        widget.add_controller(click);
+    }
+    widget
+}
+
+#[derive(Clone)]
+pub struct PopUpNotifications {
+    window: ApplicationWindow,
+    list: Box,
+    cards: Rc<RefCell<HashMap<u32, Box>>>,
+}
+
+impl PopUpNotifications {
+    pub fn new(app: &Application, daemon: &Daemon) -> Self {
+        let list = Box::new(Vertical, 10);
+        list.add_css_class("popup-notifications");
+        let window = ApplicationWindow::builder()
+            .application(app)
+            .css_classes(["popup-notifications-window"])
+            .default_width(WIDTH)
+            .child(&list)
+            .build();
+        window.init_layer_shell();
+        window.set_layer(Layer::Overlay);
+        window.set_anchor(Edge::Top, true);
+        window.set_anchor(Edge::Right, true);
+        let this = Self {
+            window,
+            list,
+            cards: Rc::new(RefCell::new(HashMap::new())),
+        };
+        let mut listener = daemon.listener();
+        spawn_future_local(clone! {
+            #[strong] this,
+            #[strong] daemon,
+            async move {
+                while let Ok(event) = listener.recv().await {
+                    match event {
+                        Event::Notified(notification) => this.add(&daemon, &notification),
+                        Event::Closed(id) => this.remove(id),
+                        Event::Changed => {}
+                    }
+                }
+            }
+        });
+        this
+    }
+    fn add(&self, daemon: &Daemon, notification: &Notification) {
+        let card = card(daemon, notification, false);
+        let mut cards = self.cards.borrow_mut();
+        // a replacement takes the place of the old one
+        match cards.insert(notification.id, card.clone()) {
+            Some(old) => {
+                self.list.insert_child_after(&card, Some(&old));
+                self.list.remove(&old);
+            }
+            None => self.list.prepend(&card),
+        }
+        self.window.present();
+    }
+    fn remove(&self, id: u32) {
+        if let Some(card) = self.cards.borrow_mut().remove(&id) {
+            self.list.remove(&card);
+        }
+        if self.cards.borrow().is_empty() {
+            self.window.hide();
+        } else {
+            // let the window shrink back to what's left
+            self.window.set_default_size(WIDTH, -1);
+        }
+    }
+}
@@ -1,52 +1,52 @@
 use crate::*;
-use anyhow::Result;
 use gtk4::Align;
-use libs::pulse::{change_channel_volume_by_percent, channel_volume_by_percent, Pulse};
+use libs::icons::Icon;
 use std::cell::Cell;
 use std::rc::Rc;
 
+/// the on screen display for a 0 to 100 value like volume or brightness,
+/// styled through `.popup-<name>` and `.popup-<name>-window`
 #[derive(Clone)]
-pub struct PopUpVolume {
+pub struct PopUpOsd {
     window: ApplicationWindow,
     hover: EventControllerMotion,
     timingout: Rc<Cell<bool>>,
+    icon: Icon,
     value: Label,
     scale: Scale,
+    icons: Rc<dyn Fn(f64) -> (String, &'static str)>,
 }
 
-impl PopUpVolume {
-    pub fn new(app: &Application) -> Self {
-        let pulse_info = Pulse::new("z3phyrl.popup-volume.info").unwrap();
-        let pulse_ctl = Pulse::new("z3phyrl.popup-volume.ctl").unwrap();
+impl PopUpOsd {
+    /// `set` is called with the percentage the scale was dragged to, `icons` gives the glyph
+    /// and the theme icon name for a value, `module` picks their style
+    pub fn new<S, I>(app: &Application, name: &str, module: &str, set: S, icons: I) -> Self
+    where
+        S: Fn(f64) + 'static,
+        I: Fn(f64) -> (String, &'static str) + 'static,
+    {
         let widget = Overlay::new();
-        widget.add_css_class("popup-volume");
+        widget.add_css_class(&format!("popup-{name}"));
         let scale = Scale::with_range(Horizontal, 0.0, 100.0, 1.0);
+        let icon = Icon::new(module);
         let value = Label::new(None);
-        scale.connect_change_value(move |s, t, v| {
-            if let Ok(info) = pulse_info.get_sink_info(0) {
-                pulse_ctl
-                    .set_sink_volume(
-                        0,
-                        channel_volume_by_percent(
-                            info.base_volume,
-                            info.cvolume.channels().len() as u32,
-                            v * 0.01,
-                        ),
-                    )
-                    .unwrap();
-            }
+        let text = Box::new(Horizontal, 5);
+        text.append(&icon.widget);
+        text.append(&value);
+        scale.connect_change_value(move |_, _, v| {
+            set(v.clamp(0.0, 100.0));
             Propagation::Proceed
         });
 
-        value.set_halign(Align::End);
-        value.set_margin_end(20);
+        text.set_halign(Align::End);
+        text.set_margin_end(20);
         scale.set_inverted(true);
         widget.add_overlay(&scale);
-        widget.add_overlay(&value);
+        widget.add_overlay(&text);
 
         let window = ApplicationWindow::builder()
             .application(app)
-            .css_classes(["popup-volume-window"])
+            .css_classes([format!("popup-{name}-window")])
             .default_width(256 + 20) // +20 is for the 10px margin on each side
             .default_height(50 + 20)
             .child(&widget)
@@ -63,8 +63,10 @@ impl PopUpVolume {
             window,
             hover: hover.clone(),
             timingout,
+            icon,
             value,
             scale,
+            icons: Rc::new(icons),
         };
         hover.connect_contains_pointer_notify(clone! {
             #[strong] this,
@@ -80,14 +82,13 @@ impl PopUpVolume {
         spawn_future_local(clone! {
             #[strong(rename_to = window)] self.window,
             #[strong(rename_to = hover)] self.hover,
-            #[strong(rename_to = timingout)]  self.timingout,
+            #[strong(rename_to = timingout)] self.timingout,
             async move {
                 let mut timeout = 200;
                 timingout.set(false);
                 sleep(Duration::from_millis(20)).await;
                 timingout.set(true);
                 while timingout.get() && timeout > 0 && !hover.contains_pointer() {
-                    println!("{timeout}");
                     sleep(Duration::from_millis(10)).await;
                     timeout -= 1;
                 }
@@ -97,8 +98,10 @@ impl PopUpVolume {
             }
         });
     }
-    pub fn update(&self, text: &str, value: f64) {
-        self.value.set_text(text);
+    pub fn update(&self, value: f64) {
+        let (glyph, name) = (self.icons)(value);
+        self.icon.set(&glyph, name);
+        self.value.set_text(&format!("{value:.0}%"));
         self.scale.set_value(value);
     }
     pub fn present(&self, present: bool) {